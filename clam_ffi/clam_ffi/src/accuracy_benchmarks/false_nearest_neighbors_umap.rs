@@ -4,13 +4,13 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use abd_clam::{graph::Graph, Cluster, Dataset, PartitionCriteria, Tree};
+use abd_clam::{graph::Graph, Cluster, Dataset};
 use glam::Vec3;
 
 use crate::{
     accuracy_benchmarks::utils::calc_fnn_scores,
-    handle::handle::Handle,
     utils::{
+        clam_tree::ClamTree,
         distances::DistanceMetric,
         scoring_functions::{enum_to_function, ScoringFunction},
         types::{DataSetf32, Graphf32, Treef32, Vertexf32},
@@ -165,12 +165,12 @@ fn run_for_each_umap(
     out_folder: &str,
     distance_metric: DistanceMetric,
 ) {
-    match Handle::create_dataset(data_name, &src_folder, distance_metric, false) {
-        Ok(data) => {
+    match utils::build_tree(data_name, src_folder, distance_metric)
+        .as_ref()
+        .map(ClamTree::as_f32)
+    {
+        Ok(Some(tree)) => {
             println!("created dataset {}", data_name);
-            let criteria = PartitionCriteria::new(true).with_min_cardinality(1);
-
-            let tree = Tree::new(data, Some(1)).partition(&criteria, None);
             println!("tree card :{}", tree.cardinality());
             println!("tree data name :{}", tree.data().name());
             let min_depth = {
@@ -181,7 +181,7 @@ fn run_for_each_umap(
                 }
             };
             if let Ok(graph) = Graph::from_tree(
-                &tree,
+                tree,
                 &enum_to_function(&ScoringFunction::LrEuclideanCc).unwrap(),
                 min_depth,
             ) {
@@ -213,7 +213,7 @@ fn run_for_each_umap(
                                             utils::extract_umap_k(positions_file).unwrap();
                                         for test_k in 3..20 {
                                             let (original_nn, umap_nn) =
-                                                umap_find_knn2(&positions, &graph, &tree, test_k);
+                                                umap_find_knn2(&positions, &graph, tree, test_k);
                                             let (precision, recall, f1_score) =
                                                 calc_fnn_scores(&original_nn, &umap_nn).unwrap();
                                             let mut file_path = PathBuf::new();
@@ -303,6 +303,9 @@ fn run_for_each_umap(
                 // eprintln!("Failed to read directory {}", dir_path);
             }
         }
+        Ok(None) => {
+            println!("{} is not an f32 dataset", data_name);
+        }
         Err(e) => {
             println!("here {:?}", e);
         }
@@ -1,4 +1,5 @@
 mod false_nearest_neighbors;
 mod false_nearest_neighbors_umap;
+pub mod runner;
 mod tests;
 mod utils;
@@ -0,0 +1,624 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use abd_clam::PartitionCriteria;
+use rand::seq::SliceRandom;
+
+use crate::{
+    graph::{
+        force_directed_graph::{ForceDirectedGraph, ForceModel},
+        graph_builder::build_force_directed_graph,
+        physics_settings::{MassModel, PhysicsSettings},
+    },
+    utils::{
+        clam_tree::ClamTree,
+        csv_readers::CsvOptions,
+        data_paths::DataPaths,
+        distances, helpers,
+        scoring_functions::{self, ScoringFunction},
+        types::Graphf32,
+    },
+};
+
+use super::{false_nearest_neighbors::clam_find_knn2, utils};
+
+/// The column names of the results table written by `write_results`
+pub const RESULT_COLUMNS: [&str; 7] = [
+    "dataset",
+    "distance_metric",
+    "depth",
+    "k",
+    "iteration",
+    "metric",
+    "value",
+];
+
+/// A measurement taken on the layout of a graph
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Benchmark {
+    /// The fraction of sampled triangles whose edges keep their order in the layout
+    EdgeEquivalence,
+    /// The mean distortion of the edge lengths of sampled triangles
+    EdgeDistortion,
+    /// The mean distortion of the angles of sampled triangles
+    AngleDistortion,
+    /// The precision, recall and f1-score of the k nearest clusters in the layout
+    Fnn,
+}
+
+impl Benchmark {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "edge_equivalence" => Ok(Benchmark::EdgeEquivalence),
+            "edge_distortion" => Ok(Benchmark::EdgeDistortion),
+            "angle_distortion" => Ok(Benchmark::AngleDistortion),
+            "fnn" => Ok(Benchmark::Fnn),
+            _ => Err(format!("unknown benchmark {}", name)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Benchmark::EdgeEquivalence => "edge_equivalence",
+            Benchmark::EdgeDistortion => "edge_distortion",
+            Benchmark::AngleDistortion => "angle_distortion",
+            Benchmark::Fnn => "fnn",
+        }
+    }
+
+    fn triangle_metric(&self) -> Option<fn(&mut [(&str, f32); 3], &mut [(&str, f32); 3]) -> f64> {
+        match self {
+            Benchmark::EdgeEquivalence => Some(utils::are_triangles_equivalent),
+            Benchmark::EdgeDistortion => Some(utils::calc_edge_distortion),
+            Benchmark::AngleDistortion => Some(utils::calc_angle_distortion),
+            Benchmark::Fnn => None,
+        }
+    }
+}
+
+/// The sweep run by `run`, read from a config file of `key = value` lines
+///
+/// Lists are comma separated and ranges of integers are written as `start..end`, with the end
+/// excluded. Blank lines and lines starting with `#` are ignored. Every key is optional:
+///
+/// ```text
+/// data_path = ../../data/anomaly_data/preprocessed
+/// datasets = wine, vertebral
+/// metrics = euclidean, manhattan
+/// depths = 4..12
+/// k = 3..20
+/// benchmarks = edge_distortion, fnn
+/// scalar = 100
+/// force_model = random_springs
+/// friction = 0.98
+/// iterations = 1200
+/// out = accuracy_results/results.csv
+/// ```
+#[derive(Debug, Clone)]
+pub struct BenchmarkConfig {
+    /// The data root holding the datasets
+    pub data_path: PathBuf,
+    /// The datasets to run on. If empty, every dataset in `data_path` is used
+    pub datasets: Vec<String>,
+    /// The distance metrics to build a tree with, by name
+    pub metrics: Vec<String>,
+    /// The min depths to select graphs at. Depths the tree does not reach are skipped
+    pub depths: Vec<usize>,
+    /// The numbers of nearest neighbors to score the fnn benchmark with
+    pub k: Vec<usize>,
+    pub benchmarks: Vec<Benchmark>,
+    pub scoring_function: ScoringFunction,
+    pub min_cardinality: usize,
+    pub scalar: f32,
+    /// The physics settings, read from the `force_model`, `friction`, `max_speed`,
+    /// `spring_constant`, `random_partners`, `mass_model`, `timestep` and `convergence_threshold`
+    /// keys. A layout that converges is measured when it does instead of after every iteration
+    pub physics: PhysicsSettings,
+    /// The number of physics iterations to run on each graph
+    pub iterations: i32,
+    /// Measure every this many iterations as well as after the last one. Zero only measures the
+    /// final layout
+    pub record_every: i32,
+    /// The number of times each cluster is sampled into a triangle by the triangle benchmarks
+    pub triangle_samples: i32,
+    pub seed: u64,
+    /// The number of worker threads. Zero uses one per available core
+    pub threads: usize,
+    /// The csv file to write the results to
+    pub out: PathBuf,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        BenchmarkConfig {
+            data_path: PathBuf::from("../../data/anomaly_data/preprocessed"),
+            datasets: Vec::new(),
+            metrics: vec!["euclidean".to_string()],
+            depths: (4..12).collect(),
+            k: (3..20).collect(),
+            benchmarks: vec![
+                Benchmark::EdgeEquivalence,
+                Benchmark::EdgeDistortion,
+                Benchmark::AngleDistortion,
+                Benchmark::Fnn,
+            ],
+            scoring_function: ScoringFunction::LrEuclideanCc,
+            min_cardinality: 1,
+            scalar: 100.0,
+            physics: PhysicsSettings::default(),
+            iterations: 1200,
+            record_every: 0,
+            triangle_samples: 5,
+            seed: utils::SEED,
+            threads: 0,
+            out: PathBuf::from("accuracy_results/results.csv"),
+        }
+    }
+}
+
+impl BenchmarkConfig {
+    /// Function to read a config file
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the config or a message naming the line that could not be read
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut config = BenchmarkConfig::default();
+
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected key = value", i + 1))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            config
+                .set(key, value)
+                .map_err(|e| format!("line {}: {}", i + 1, e))?;
+        }
+
+        for metric in &config.metrics {
+            distances::from_name(metric).map_err(|_| format!("unknown metric {}", metric))?;
+        }
+        if !config.physics.is_valid() {
+            return Err("invalid physics settings".to_string());
+        }
+        Ok(config)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "data_path" => self.data_path = PathBuf::from(value),
+            "datasets" => self.datasets = parse_list(value, |s| Ok(s.to_string()))?,
+            "metrics" => self.metrics = parse_list(value, |s| Ok(s.to_string()))?,
+            "depths" => self.depths = parse_range(value)?,
+            "k" => self.k = parse_range(value)?,
+            "benchmarks" => self.benchmarks = parse_list(value, Benchmark::from_name)?,
+            "scoring_function" => {
+                self.scoring_function = scoring_functions::string_to_enum(value)
+                    .map_err(|_| format!("unknown scoring function {}", value))?
+            }
+            "min_cardinality" => self.min_cardinality = parse_value(value)?,
+            "scalar" => self.scalar = parse_value(value)?,
+            "force_model" => self.physics.force_model = ForceModel::from_name(value)?,
+            "friction" => self.physics.friction = parse_value(value)?,
+            "max_speed" => self.physics.max_speed = parse_value(value)?,
+            "spring_constant" => self.physics.spring_constant = parse_value(value)?,
+            "random_partners" => self.physics.random_partners = parse_value(value)?,
+            "mass_model" => self.physics.mass_model = MassModel::from_name(value)?,
+            "timestep" => self.physics.timestep = parse_value(value)?,
+            "convergence_threshold" => self.physics.convergence_threshold = parse_value(value)?,
+            "iterations" => self.iterations = parse_value(value)?,
+            "record_every" => self.record_every = parse_value(value)?,
+            "triangle_samples" => self.triangle_samples = parse_value(value)?,
+            "seed" => self.seed = parse_value(value)?,
+            "threads" => self.threads = parse_value(value)?,
+            "out" => self.out = PathBuf::from(value),
+            _ => return Err(format!("unknown key {}", key)),
+        }
+        Ok(())
+    }
+
+    /// Function to list the datasets to run on, scanning `data_path` if none are given
+    pub fn dataset_names(&self) -> Result<Vec<String>, String> {
+        if !self.datasets.is_empty() {
+            return Ok(self.datasets.clone());
+        }
+
+        let entries = fs::read_dir(&self.data_path)
+            .map_err(|e| format!("could not read {}: {}", self.data_path.display(), e))?;
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name();
+                let file_name = file_name.to_str()?;
+                file_name
+                    .strip_suffix("_features.npy")
+                    .map(|name| name.to_string())
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn thread_count(&self) -> usize {
+        match self.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        }
+    }
+}
+
+/// One row of the results table
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkRow {
+    pub dataset: String,
+    pub distance_metric: String,
+    pub depth: usize,
+    /// The number of nearest neighbors, for the fnn benchmark only
+    pub k: Option<usize>,
+    pub iteration: i32,
+    pub metric: String,
+    pub value: f64,
+}
+
+/// Function to run every benchmark of a config on every dataset, metric and depth
+///
+/// Trees are built in parallel, one per dataset and metric, and the graphs are then laid out in
+/// parallel, one per tree and depth. Datasets and graphs that fail are reported and skipped.
+///
+/// # Returns
+///
+/// The rows of the results table, sorted by dataset, metric, depth, k, iteration and metric
+pub fn run(config: &BenchmarkConfig) -> Result<Vec<BenchmarkRow>, String> {
+    let datasets = config.dataset_names()?;
+    let tree_jobs: Vec<(&String, &String)> = datasets
+        .iter()
+        .flat_map(|dataset| config.metrics.iter().map(move |metric| (dataset, metric)))
+        .collect();
+
+    let trees = run_parallel(&tree_jobs, config.thread_count(), |&(dataset, metric)| {
+        println!("building tree for {} with {}", dataset, metric);
+        build_tree(config, dataset, metric)
+            .map(|tree| (dataset, metric, tree))
+            .map_err(|e| eprintln!("skipping {} with {}: {}", dataset, metric, e))
+            .ok()
+    });
+    let trees: Vec<_> = trees.into_iter().flatten().collect();
+
+    let graph_jobs: Vec<(usize, usize)> = (0..trees.len())
+        .flat_map(|i| config.depths.iter().map(move |&depth| (i, depth)))
+        .filter(|&(i, depth)| depth <= trees[i].2.depth())
+        .collect();
+
+    let results = run_parallel(&graph_jobs, config.thread_count(), |&(i, depth)| {
+        let (dataset, metric, tree) = &trees[i];
+        println!("running {} with {} at depth {}", dataset, metric, depth);
+        run_graph(config, tree, depth)
+            .map_err(|e| {
+                eprintln!(
+                    "skipping {} with {} at depth {}: {}",
+                    dataset, metric, depth, e
+                )
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(k, iteration, name, value)| BenchmarkRow {
+                dataset: dataset.to_string(),
+                distance_metric: metric.to_string(),
+                depth,
+                k,
+                iteration,
+                metric: name,
+                value,
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut rows: Vec<BenchmarkRow> = results.into_iter().flatten().collect();
+    rows.sort_by(|a, b| {
+        (
+            &a.dataset,
+            &a.distance_metric,
+            a.depth,
+            a.k,
+            a.iteration,
+            &a.metric,
+        )
+            .cmp(&(
+                &b.dataset,
+                &b.distance_metric,
+                b.depth,
+                b.k,
+                b.iteration,
+                &b.metric,
+            ))
+    });
+    Ok(rows)
+}
+
+/// Function to write the rows to a csv file with one measurement per row
+pub fn write_results(path: &Path, rows: &[BenchmarkRow]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut writer = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+
+    writer
+        .write_record(RESULT_COLUMNS)
+        .map_err(|e| e.to_string())?;
+    for row in rows {
+        writer
+            .write_record([
+                row.dataset.clone(),
+                row.distance_metric.clone(),
+                row.depth.to_string(),
+                row.k.map_or(String::new(), |k| k.to_string()),
+                row.iteration.to_string(),
+                row.metric.clone(),
+                row.value.to_string(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+fn build_tree(config: &BenchmarkConfig, dataset: &str, metric: &str) -> Result<ClamTree, String> {
+    let metric = distances::from_name(metric).map_err(|e| format!("{:?}", e))?;
+    let data_paths = DataPaths::resolve(dataset, Some(&config.data_path), None, false)
+        .map_err(|e| format!("{:?}", e))?;
+    let criteria = PartitionCriteria::new(true).with_min_cardinality(config.min_cardinality);
+
+    ClamTree::build(
+        dataset,
+        &data_paths,
+        &CsvOptions::default(),
+        metric,
+        &distances::MetricParams::default(),
+        false,
+        &criteria,
+        Some(config.seed),
+    )
+    .map(|(tree, _)| tree)
+    .map_err(|e| format!("{:?}", e))
+}
+
+/// Function to lay out the graph selected at a depth and measure it
+///
+/// # Returns
+///
+/// A `Result` containing the k, iteration, metric name and value of each measurement
+fn run_graph(
+    config: &BenchmarkConfig,
+    tree: &ClamTree,
+    depth: usize,
+) -> Result<Vec<(Option<usize>, i32, String, f64)>, String> {
+    let scorer = scoring_functions::enum_to_function(&config.scoring_function)
+        .map_err(|e| format!("{:?}", e))?;
+    let graph = tree.build_graph(&scorer, depth)?;
+    if graph.vertex_cardinality() < 3 {
+        return Err("less than 3 clusters in graph".to_string());
+    }
+
+    let mut measurements = vec![
+        (
+            None,
+            0,
+            "graph_vertex_cardinality".to_string(),
+            graph.vertex_cardinality() as f64,
+        ),
+        (
+            None,
+            0,
+            "graph_edge_cardinality".to_string(),
+            graph.edge_cardinality() as f64,
+        ),
+    ];
+
+    let mut fdg = build_force_directed_graph(
+        tree,
+        &graph,
+        config.scalar,
+        config.physics,
+        config.iterations,
+        Some(config.seed),
+    );
+    for i in 1..=config.iterations {
+        fdg.update(&graph, tree);
+        let is_converged = fdg.converged_at().is_some();
+        let is_recorded = config.record_every > 0 && i % config.record_every == 0;
+        if is_recorded || i == config.iterations || is_converged {
+            measure(config, tree, &graph, &fdg, i, &mut measurements)?;
+        }
+        if is_converged {
+            measurements.push((None, i, "iterations_to_converge".to_string(), i as f64));
+            break;
+        }
+    }
+
+    Ok(measurements)
+}
+
+fn measure(
+    config: &BenchmarkConfig,
+    tree: &ClamTree,
+    graph: &Graphf32,
+    fdg: &ForceDirectedGraph,
+    iteration: i32,
+    measurements: &mut Vec<(Option<usize>, i32, String, f64)>,
+) -> Result<(), String> {
+    for benchmark in &config.benchmarks {
+        if let Some(metric_cb) = benchmark.triangle_metric() {
+            let value = run_triangle_test(tree, graph, fdg, config.triangle_samples, metric_cb)?;
+            measurements.push((None, iteration, benchmark.name().to_string(), value));
+            continue;
+        }
+
+        for &k in &config.k {
+            let (original_nn, fdg_nn) = clam_find_knn2(fdg, graph, tree, k);
+            let (precision, recall, f1_score) = utils::calc_fnn_scores(&original_nn, &fdg_nn)?;
+            for (name, value) in [
+                ("fnn_precision", precision),
+                ("fnn_recall", recall),
+                ("fnn_f1_score", f1_score),
+            ] {
+                measurements.push((Some(k), iteration, name.to_string(), value));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn run_triangle_test(
+    tree: &ClamTree,
+    clam_graph: &Graphf32,
+    fdg: &ForceDirectedGraph,
+    num_test_iters: i32,
+    metric_cb: fn(&mut [(&str, f32); 3], &mut [(&str, f32); 3]) -> f64,
+) -> Result<f64, String> {
+    if clam_graph.ordered_clusters().len() < 3 {
+        return Err("less than 3 clusters in graph".to_string());
+    }
+    let mut clusters: Vec<_> = clam_graph
+        .ordered_clusters()
+        .into_iter()
+        .map(|c| *c)
+        .collect();
+    let mut rng = helpers::seeded_rng(Some(utils::SEED));
+    let mut metric_sum: f64 = 0.;
+
+    let mut valid_count = 0;
+    for _ in 0..num_test_iters {
+        for a in clam_graph.ordered_clusters() {
+            clusters.partial_shuffle(&mut rng, 5);
+            if let Some(chosen_clusters) = utils::choose_two_random_clusters_exclusive(&clusters, a)
+            {
+                if let Ok(mut clam_edges) = utils::triangle_from_clusters(tree, &chosen_clusters) {
+                    if let Ok(mut unity_edges) = utils::get_unity_triangle(&chosen_clusters, fdg) {
+                        metric_sum += metric_cb(&mut clam_edges, &mut unity_edges);
+                        valid_count += 1;
+                    }
+                }
+            }
+        }
+    }
+    if valid_count == 0 {
+        return Err("no valid triangles found".to_string());
+    }
+
+    Ok(metric_sum / valid_count as f64)
+}
+
+/// Function to map the items on a pool of scoped threads
+///
+/// # Returns
+///
+/// The results in the order of the items
+fn run_parallel<T: Sync, R: Send>(
+    items: &[T],
+    threads: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                if i >= items.len() {
+                    break;
+                }
+                let result = f(&items[i]);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.unwrap())
+        .collect()
+}
+
+fn parse_list<T>(value: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(parse)
+        .collect()
+}
+
+/// Function to parse a `start..end` range or a list of integers
+fn parse_range(value: &str) -> Result<Vec<usize>, String> {
+    match value.split_once("..") {
+        Some((start, end)) => Ok((parse_value(start.trim())?..parse_value(end.trim())?).collect()),
+        None => parse_list(value, parse_value),
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = BenchmarkConfig::parse(
+            "# sweep\n\
+             datasets = wine, vertebral\n\
+             metrics = euclidean,manhattan\n\
+             depths = 4..7\n\
+             k = 3, 5, 10\n\
+             benchmarks = edge_distortion, fnn\n\
+             \n\
+             iterations = 50\n\
+             force_model = barnes_hut\n\
+             mass_model = uniform\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.datasets, vec!["wine", "vertebral"]);
+        assert_eq!(config.metrics, vec!["euclidean", "manhattan"]);
+        assert_eq!(config.depths, vec![4, 5, 6]);
+        assert_eq!(config.k, vec![3, 5, 10]);
+        assert_eq!(
+            config.benchmarks,
+            vec![Benchmark::EdgeDistortion, Benchmark::Fnn]
+        );
+        assert_eq!(config.iterations, 50);
+        assert_eq!(config.scalar, 100.0);
+        assert_eq!(config.physics.force_model, ForceModel::BarnesHut);
+        assert_eq!(config.physics.mass_model, MassModel::Uniform);
+        assert_eq!(config.physics.friction, 0.98);
+
+        assert!(BenchmarkConfig::parse("depths = 4..x").is_err());
+        assert!(BenchmarkConfig::parse("metrics = nope").is_err());
+        assert!(BenchmarkConfig::parse("unknown = 1").is_err());
+        assert!(BenchmarkConfig::parse("friction = 2").is_err());
+    }
+}
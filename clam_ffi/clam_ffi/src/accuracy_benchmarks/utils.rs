@@ -6,20 +6,58 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use abd_clam::{graph::Vertex, Cluster};
+use abd_clam::{graph::Vertex, Cluster, PartitionCriteria};
 use csv::{Writer, WriterBuilder};
 use distances::Number;
-use rand::{rngs::ThreadRng, seq::SliceRandom, thread_rng};
+use rand::{rngs::StdRng, seq::SliceRandom};
 
 use crate::{
     ffi_impl::cluster_data_wrapper::ClusterDataWrapper,
     graph::force_directed_graph::ForceDirectedGraph,
-    utils::types::{Treef32, Vertexf32},
+    utils::{
+        clam_tree::ClamTree,
+        csv_readers::CsvOptions,
+        data_paths::DataPaths,
+        distances::{DistanceMetric, MetricParams},
+        error::FFIError,
+        types::Vertexf32,
+    },
     CBFnNodeVisitorMut,
 };
 
 use super::false_nearest_neighbors::FNN_Wrapper;
 
+/// The seed for the trees, layouts and sampling of the benchmarks, so their results can be repeated
+pub const SEED: u64 = 1;
+
+/// Function to build a tree over a labeled dataset, partitioned down to single instances
+///
+/// # Arguments
+///
+/// * `data_name` - The name of the dataset
+/// * `src_folder` - The folder holding the dataset files
+/// * `distance_metric` - The distance metric to use
+pub fn build_tree(
+    data_name: &str,
+    src_folder: &Path,
+    distance_metric: DistanceMetric,
+) -> Result<ClamTree, FFIError> {
+    let data_paths = DataPaths::resolve(data_name, Some(src_folder), None, true)?;
+    let criteria = PartitionCriteria::new(true).with_min_cardinality(1);
+
+    ClamTree::build(
+        data_name,
+        &data_paths,
+        &CsvOptions::default(),
+        distance_metric,
+        &MetricParams::default(),
+        false,
+        &criteria,
+        Some(SEED),
+    )
+    .map(|(tree, _)| tree)
+}
+
 pub fn choose_two_random_clusters_exclusive<'a, U: Number>(
     clusters: &Vec<&'a Vertex<U>>,
     cluster: &'a Vertex<U>,
@@ -38,22 +76,13 @@ pub fn choose_two_random_clusters_exclusive<'a, U: Number>(
     return None;
 }
 pub fn triangle_from_clusters<'a>(
-    tree: &Treef32,
+    tree: &ClamTree,
     clusters: &[&'a Vertexf32; 3],
 ) -> Result<[(&'a str, f32); 3], String> {
     let triangle = [
-        (
-            "ab",
-            clusters[0].distance_to_other(tree.data(), clusters[1]),
-        ),
-        (
-            "ac",
-            clusters[0].distance_to_other(tree.data(), clusters[2]),
-        ),
-        (
-            "bc",
-            clusters[1].distance_to_other(tree.data(), clusters[2]),
-        ),
+        ("ab", tree.cluster_distance(clusters[0], clusters[1])),
+        ("ac", tree.cluster_distance(clusters[0], clusters[2])),
+        ("bc", tree.cluster_distance(clusters[1], clusters[2])),
     ];
     if is_valid_triangle(&triangle) {
         return Ok(triangle);
@@ -320,7 +349,7 @@ pub fn positions_to_distances<'a>(
 
 pub fn randomly_select_three_indices(
     range: &mut Vec<usize>,
-    mut rng: &mut ThreadRng,
+    mut rng: &mut StdRng,
 ) -> (usize, usize, usize) {
     range.partial_shuffle(&mut rng, 3);
 
@@ -0,0 +1,571 @@
+//! Headless command line interface for building trees, graphs and layouts without Unity
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use abd_clam::Cluster;
+use clam_ffi::accuracy_benchmarks::runner::{self, BenchmarkConfig};
+use clam_ffi::ffi_impl::partition_criteria_ffi::PartitionCriteriaFFI;
+use clam_ffi::file_io::graph_export::{GraphExport, GraphFormat};
+use clam_ffi::file_io::mesh_export::{
+    MeshExportOptions, MeshFormat, MeshLayout, MeshScene, NodeSizing,
+};
+use clam_ffi::file_io::tree_render::{self, ImageFormat, NodeLabel, TreeRenderOptions};
+use clam_ffi::file_io::{graph_layout::GraphLayout, load_save};
+use clam_ffi::graph::force_directed_graph::ForceModel;
+use clam_ffi::graph::graph_builder::build_force_directed_graph;
+use clam_ffi::graph::graph_selection::SelectionMode;
+use clam_ffi::graph::physics_settings::{MassModel, PhysicsSettings};
+use clam_ffi::handle::handle::Handle;
+use clam_ffi::utils::coloring::ClusterColoring;
+use clam_ffi::utils::csv_readers::CsvOptions;
+use clam_ffi::utils::data_paths::DataPaths;
+use clam_ffi::utils::data_types::DataType;
+use clam_ffi::utils::distances::{self, MetricParams};
+use clam_ffi::utils::error::FFIError;
+use clam_ffi::utils::scoring_functions;
+
+const USAGE: &str = "\
+usage: clam_visual <command> [options]
+
+commands:
+  build <data_name> --out <tree>     build a tree from a dataset and save it
+  graph <data_name> [--out <file>]   select a graph, print its size and optionally export it
+  layout <data_name> --out <file>    select a graph, run the physics and write the positions
+  tree <data_name> --out <file>      draw the Reingold-Tilford layout of the tree as an image or mesh
+  benchmark <config>                 run the accuracy benchmarks listed in a config file
+
+dataset options:
+  --data-path <path>         data root or features file (default: the repo data folder)
+  --labels-path <path>       labels file (default: next to the features file)
+  --features-only            the dataset has no labels
+  --delimiter <char>         field delimiter of a .csv/.tsv file
+  --has-header               the first row of a .csv/.tsv file is a header
+  --label-column <index>     column of a .csv/.tsv file holding the labels (default: -1)
+  --tree <path>              load a saved tree instead of building one (not build)
+  --data-type <type>         element type of a saved tree: f32, f64, u8, i32, bits, string
+  --metric <name>            distance metric (default: euclidean)
+  --minkowski-p <p>          p of the minkowski metric (default: 2)
+  --expensive                the distance metric is expensive
+
+partition options:
+  --min-cardinality <n>      (default: 1)
+  --max-depth <n>
+  --min-radius <r>
+  --min-lfd <lfd>
+  --check-any                partition clusters that pass any criterion instead of all of them
+  --seed <n>                 seed for the tree, graph layout and physics (default: 1)
+
+graph options:
+  --scoring-function <name>  e.g. lr_euclidean_cc (default: lr_euclidean_cc)
+  --min-depth <n>            (default: 4)
+  --selection <mode>         choose the clusters without a scorer, overriding the two options
+                             above: \"depth <d>\", \"leaf_cut <count>\",
+                             \"threshold <max radius> <max lfd>\" or \"ids <offset-card> ...\"
+
+layout options:
+  --iterations <n>           most physics iterations, fewer if the layout converges (default: 1000)
+  --scalar <s>               scale of the layout (default: 100)
+  --force-model <name>       forces between clusters without an edge: random_springs or
+                             barnes_hut (default: random_springs)
+  --friction <f>             fraction of its velocity a cluster keeps per timestep (default: 0.98)
+  --max-speed <s>            (default: 5)
+  --spring-constant <k>      stiffness of the springs (default: 0.005)
+  --random-partners <n>      random clusters each cluster is pulled towards per frame (default: 3)
+  --mass-model <name>        cardinality, log_cardinality or uniform (default: cardinality)
+  --timestep <t>             (default: 1)
+  --convergence-threshold <f>
+                             stop once the energy changes by at most this fraction per frame,
+                             or 0 to run every iteration (default: 0.0001)
+
+drawing options (layout and tree):
+  --coloring <name>          uniform, dominant_label or entropy (default: uniform)
+  --draw-depth <n>           deepest clusters drawn by tree (default: the whole tree)
+
+mesh options:
+  --sizing <name>            size clusters by radius or cardinality (default: radius)
+  --node-scale <s>           radius of the sphere of the root (default: 1)
+
+image options (tree):
+  --labels <name>            text next to every cluster: none, name or cardinality (default: none)
+  --spacing <px>             distance between levels of the tree (default: 20)
+  --node-radius <px>         radius of every cluster (default: 5)
+  --png-scale <s>            size of a png relative to the svg (default: 1)
+
+benchmark options:
+  --out <file>               results csv, overriding the `out` of the config
+  --threads <n>              worker threads, overriding the `threads` of the config
+
+Graphs are exported as GraphML, GEXF or DOT by the extension of the output file (.graphml, .gexf,
+.dot) and meshes as glTF, OBJ or PLY (.gltf, .obj, .ply). Trees are drawn as SVG or PNG images (.svg, .png)
+or as meshes. Layouts are written in the format read by
+`load_graph_layout`, as `id,x,y,z` rows if the output file ends in .csv, or as a graph or mesh
+export with positions for those extensions. See accuracy_benchmarks.cfg for the keys of a
+benchmark config";
+
+/// Options that do not take a value
+const FLAGS: [&str; 5] = [
+    "features-only",
+    "has-header",
+    "expensive",
+    "check-any",
+    "help",
+];
+
+/// Options that take a value
+const OPTIONS: [&str; 36] = [
+    "data-path",
+    "labels-path",
+    "delimiter",
+    "label-column",
+    "tree",
+    "data-type",
+    "metric",
+    "minkowski-p",
+    "min-cardinality",
+    "max-depth",
+    "min-radius",
+    "min-lfd",
+    "seed",
+    "scoring-function",
+    "min-depth",
+    "selection",
+    "iterations",
+    "scalar",
+    "force-model",
+    "friction",
+    "max-speed",
+    "spring-constant",
+    "random-partners",
+    "mass-model",
+    "timestep",
+    "convergence-threshold",
+    "coloring",
+    "draw-depth",
+    "sizing",
+    "node-scale",
+    "labels",
+    "spacing",
+    "node-radius",
+    "png-scale",
+    "out",
+    "threads",
+];
+
+/// The positional arguments, options and flags passed to a command
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                let value = if FLAGS.contains(&name) {
+                    String::new()
+                } else if OPTIONS.contains(&name) {
+                    args.next()
+                        .ok_or_else(|| format!("--{} needs a value", name))?
+                } else {
+                    return Err(format!("unknown option --{}", name));
+                };
+                options.insert(name.to_string(), value);
+            } else {
+                positional.push(arg);
+            }
+        }
+
+        Ok(Args {
+            positional,
+            options,
+        })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn parse_or<T: std::str::FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        match self.get(name) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| format!("invalid value for --{}: {}", name, value)),
+            None => Ok(default),
+        }
+    }
+
+    fn required(&self, name: &str) -> Result<&str, String> {
+        self.get(name)
+            .ok_or_else(|| format!("--{} is required", name))
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+
+    let result = Args::parse(args).and_then(|args| match command.as_deref() {
+        _ if args.flag("help") => Err(String::new()),
+        Some("build") => build(&args),
+        Some("graph") => graph(&args),
+        Some("layout") => layout(&args),
+        Some("tree") => tree(&args),
+        Some("benchmark") => benchmark(&args),
+        Some(command) => Err(format!("unknown command {}", command)),
+        None => Err(String::new()),
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("error: {}\n", e);
+            }
+            eprintln!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Builds a tree from a dataset and saves it
+fn build(args: &Args) -> Result<(), String> {
+    if args.get("tree").is_some() {
+        return Err("build reads a dataset, not a saved tree".to_string());
+    }
+    let out = args.required("out")?;
+    let handle = create_handle(args)?;
+
+    check(load_save::save_tree(&handle, Path::new(out)))?;
+    let tree = handle.tree().unwrap();
+    println!(
+        "saved tree of {} instances with depth {} to {}",
+        tree.cardinality(),
+        tree.depth(),
+        out
+    );
+    Ok(())
+}
+
+/// Selects a graph from a tree and prints its size
+fn graph(args: &Args) -> Result<(), String> {
+    let mut handle = create_handle(args)?;
+    select_graph(&mut handle, args)?;
+    let mode = handle.graph_selection().unwrap().mode().clone();
+    let clam_graph = check_result(handle.clam_graph().ok_or(FFIError::GraphBuildFailed))?;
+
+    println!(
+        "selected {} clusters with {} edges in {} components with {}",
+        clam_graph.vertex_cardinality(),
+        clam_graph.edge_cardinality(),
+        clam_graph.find_component_clusters().len(),
+        mode
+    );
+
+    if let Some(out) = args.get("out") {
+        let out = Path::new(out);
+        let format = check_result(GraphFormat::from_path(out))?;
+        check(handle.export_graph(out, format))?;
+        println!("exported the graph to {}", out.display());
+    }
+    Ok(())
+}
+
+/// Selects a graph, runs the physics on it and writes the positions
+fn layout(args: &Args) -> Result<(), String> {
+    let out = PathBuf::from(args.required("out")?);
+    let iterations: i32 = args.parse_or("iterations", 1000)?;
+    let scalar: f32 = args.parse_or("scalar", 100.0)?;
+    let settings = physics_settings(args)?;
+
+    let mut handle = create_handle(args)?;
+    select_graph(&mut handle, args)?;
+    let selection = handle.graph_selection().unwrap();
+    let clam_graph = check_result(handle.clam_graph().ok_or(FFIError::GraphBuildFailed))?;
+    let tree = handle.tree().unwrap();
+
+    let mut force_directed_graph = build_force_directed_graph(
+        tree,
+        &clam_graph,
+        scalar,
+        settings,
+        iterations,
+        handle.seed(),
+    );
+    let mut ran = 0;
+    while ran < iterations && force_directed_graph.converged_at().is_none() {
+        force_directed_graph.update(&clam_graph, tree);
+        ran += 1;
+    }
+
+    let layout = GraphLayout::capture(&force_directed_graph, selection.mode().clone());
+    if let Ok(format) = MeshFormat::from_path(&out) {
+        let options = mesh_options(args, MeshLayout::Graph)?;
+        let scene = check_result(MeshScene::from_layout(&handle, &layout, &options))?;
+        check_result(scene.save(&out, format))?;
+    } else if let Ok(format) = GraphFormat::from_path(&out) {
+        let export = GraphExport::new(tree, &clam_graph, handle.labels(), Some(&layout));
+        check_result(export.save(&out, format))?;
+    } else if out.extension().map_or(false, |ext| ext == "csv") {
+        let mut contents = String::from("id,x,y,z\n");
+        for (id, position) in &layout.positions {
+            contents.push_str(&format!(
+                "{},{},{},{}\n",
+                id, position.x, position.y, position.z
+            ));
+        }
+        fs::write(&out, contents).map_err(|e| e.to_string())?;
+    } else {
+        check_result(layout.save(&out))?;
+    }
+
+    println!(
+        "wrote {} positions after {} iterations to {}",
+        layout.positions.len(),
+        ran,
+        out.display()
+    );
+    if force_directed_graph.converged_at().is_some() {
+        println!("the layout converged");
+    }
+    let stats = force_directed_graph.distance_cache_stats();
+    if stats.hits + stats.metric_calls > 0 {
+        println!(
+            "cached {} distances: {} hits and {} metric calls ({:.1}% hit rate)",
+            stats.entries,
+            stats.hits,
+            stats.metric_calls,
+            100.0 * stats.hit_rate
+        );
+    }
+    Ok(())
+}
+
+/// Lays out the tree and draws it as an image or writes it as a mesh
+fn tree(args: &Args) -> Result<(), String> {
+    let out = PathBuf::from(args.required("out")?);
+    if let Ok(format) = ImageFormat::from_path(&out) {
+        let options = TreeRenderOptions {
+            coloring: ClusterColoring::from_name(args.get("coloring").unwrap_or("uniform"))?,
+            label: NodeLabel::from_name(args.get("labels").unwrap_or("none"))?,
+            max_depth: args.parse_or("draw-depth", -1)?,
+            spacing: args.parse_or("spacing", 20.0)?,
+            node_radius: args.parse_or("node-radius", 5.0)?,
+            png_scale: args.parse_or("png-scale", 1.0)?,
+        };
+
+        let handle = create_handle(args)?;
+        check_result(tree_render::save_tree_render(
+            &handle, &out, format, &options,
+        ))?;
+        println!("drew the tree to {}", out.display());
+        return Ok(());
+    }
+
+    let format = check_result(MeshFormat::from_path(&out))?;
+    let options = mesh_options(args, MeshLayout::Tree)?;
+
+    let handle = create_handle(args)?;
+    let scene = check_result(MeshScene::new(&handle, &options))?;
+    check_result(scene.save(&out, format))?;
+
+    println!(
+        "wrote {} clusters and {} edges to {}",
+        scene.spheres.len(),
+        scene.lines.len(),
+        out.display()
+    );
+    Ok(())
+}
+
+/// Runs the accuracy benchmarks of a config file and writes the results table
+fn benchmark(args: &Args) -> Result<(), String> {
+    let path = args.positional.first().ok_or("a config file is required")?;
+    let mut config = BenchmarkConfig::load(Path::new(path))?;
+    if let Some(out) = args.get("out") {
+        config.out = PathBuf::from(out);
+    }
+    config.threads = args.parse_or("threads", config.threads)?;
+
+    let rows = runner::run(&config)?;
+    runner::write_results(&config.out, &rows)?;
+    println!("wrote {} results to {}", rows.len(), config.out.display());
+    Ok(())
+}
+
+/// Builds a handle from a dataset, or loads it from a saved tree if `--tree` is given
+fn create_handle(args: &Args) -> Result<Handle, String> {
+    let metric = distances::from_name(args.get("metric").unwrap_or("euclidean"))
+        .map_err(|e| format!("{:?}", e))?;
+    let params = MetricParams {
+        minkowski_p: args.parse_or("minkowski-p", distances::DEFAULT_MINKOWSKI_P)?,
+        ..Default::default()
+    };
+    let is_expensive = args.flag("expensive");
+    let seed: i64 = args.parse_or("seed", 1)?;
+    let seed = (seed >= 0).then_some(seed as u64);
+
+    if let Some(tree_path) = args.get("tree") {
+        let data_type = DataType::from_name(args.get("data-type").unwrap_or("f32"))
+            .map_err(|e| format!("{:?}", e))?;
+        return check_result(Handle::load(
+            Path::new(tree_path),
+            data_type,
+            metric,
+            &params,
+            is_expensive,
+            !args.flag("features-only"),
+            seed,
+        ));
+    }
+
+    let data_name = args
+        .positional
+        .first()
+        .ok_or("a dataset name or --tree is required")?;
+    let data_paths = check_result(DataPaths::resolve(
+        data_name,
+        args.get("data-path").map(Path::new),
+        args.get("labels-path").map(Path::new),
+        !args.flag("features-only"),
+    ))?;
+    let csv_options = CsvOptions {
+        delimiter: match args.get("delimiter") {
+            Some(delimiter) if delimiter.len() == 1 => Some(delimiter.as_bytes()[0]),
+            Some(delimiter) => return Err(format!("invalid delimiter {}", delimiter)),
+            None => None,
+        },
+        has_header: args.flag("has-header"),
+        label_column: (!args.flag("features-only")).then_some(args.parse_or("label-column", -1)?),
+    };
+
+    let partition_criteria = PartitionCriteriaFFI {
+        check_any: args.flag("check-any"),
+        max_depth: args.parse_or("max-depth", 0)?,
+        min_radius: args.parse_or("min-radius", 0.0)?,
+        min_lfd: args.parse_or("min-lfd", 0.0)?,
+        seed: seed.map_or(-1, |seed| seed as i64),
+    };
+    let min_cardinality = args.parse_or("min-cardinality", 1)?;
+
+    check_result(Handle::new(
+        data_name,
+        &data_paths,
+        &csv_options,
+        metric,
+        &params,
+        is_expensive,
+        &partition_criteria.criteria(min_cardinality),
+        partition_criteria.seed(),
+    ))
+}
+
+/// Reads the physics settings in the args, starting from the defaults
+fn physics_settings(args: &Args) -> Result<PhysicsSettings, String> {
+    let defaults = PhysicsSettings::default();
+    let settings = PhysicsSettings {
+        friction: args.parse_or("friction", defaults.friction)?,
+        max_speed: args.parse_or("max-speed", defaults.max_speed)?,
+        spring_constant: args.parse_or("spring-constant", defaults.spring_constant)?,
+        random_partners: args.parse_or("random-partners", defaults.random_partners)?,
+        mass_model: MassModel::from_name(args.get("mass-model").unwrap_or("cardinality"))?,
+        force_model: ForceModel::from_name(args.get("force-model").unwrap_or("random_springs"))?,
+        timestep: args.parse_or("timestep", defaults.timestep)?,
+        convergence_threshold: args
+            .parse_or("convergence-threshold", defaults.convergence_threshold)?,
+    };
+    if !settings.is_valid() {
+        return Err("invalid physics settings".to_string());
+    }
+    Ok(settings)
+}
+
+/// Reads the mesh options in the args
+fn mesh_options(args: &Args, layout: MeshLayout) -> Result<MeshExportOptions, String> {
+    Ok(MeshExportOptions {
+        layout,
+        coloring: ClusterColoring::from_name(args.get("coloring").unwrap_or("uniform"))?,
+        sizing: NodeSizing::from_name(args.get("sizing").unwrap_or("radius"))?,
+        node_scale: args.parse_or("node-scale", 1.0)?,
+        max_depth: args.parse_or("draw-depth", -1)?,
+    })
+}
+
+/// Selects a graph on a handle with the selection mode, or the scoring function and min depth, in
+/// the args
+fn select_graph(handle: &mut Handle, args: &Args) -> Result<(), String> {
+    let mode = match args.get("selection") {
+        Some(mode) => SelectionMode::parse(mode)?,
+        None => {
+            let name = args.get("scoring-function").unwrap_or("lr_euclidean_cc");
+            let scoring_function = scoring_functions::string_to_enum(name)
+                .map_err(|_| format!("unknown scoring function {}", name))?;
+            SelectionMode::Scorer(scoring_function, args.parse_or("min-depth", 4)?)
+        }
+    };
+
+    let selection = check_result(handle.select_graph_with(mode.clone()))?;
+    if selection.vertex_cardinality() == 0 {
+        return Err(format!(
+            "no clusters selected with {} from {}",
+            mode,
+            handle.root().unwrap().name()
+        ));
+    }
+
+    Ok(())
+}
+
+fn check(e: FFIError) -> Result<(), String> {
+    match e {
+        FFIError::Ok => Ok(()),
+        e => Err(format!("{:?}", e)),
+    }
+}
+
+fn check_result<T>(result: Result<T, FFIError>) -> Result<T, String> {
+    result.map_err(|e| format!("{:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let args = Args::parse(
+            [
+                "wine",
+                "--min-depth",
+                "6",
+                "--features-only",
+                "--out",
+                "wine.layout",
+            ]
+            .map(String::from),
+        )
+        .unwrap();
+
+        assert_eq!(args.positional, vec!["wine".to_string()]);
+        assert_eq!(args.parse_or("min-depth", 4).unwrap(), 6);
+        assert_eq!(args.parse_or("iterations", 1000).unwrap(), 1000);
+        assert!(args.flag("features-only"));
+        assert_eq!(args.required("out").unwrap(), "wine.layout");
+        assert!(args.required("tree").is_err());
+        assert!(Args::parse(["--out"].map(String::from)).is_err());
+        assert_eq!(
+            Args::parse(["wine", "--min-depht", "6"].map(String::from)).err(),
+            Some("unknown option --min-depht".to_string())
+        );
+    }
+}
@@ -6,9 +6,11 @@ use abd_clam::Cluster;
 use distances::Number;
 
 use crate::ffi_impl::cleanup::Cleanup;
+use crate::graph::graph_selection::SelectionMode;
 use crate::{
     debug,
     utils::{
+        custom_metrics::{self, CBFnDistance},
         error::FFIError,
         helpers,
         types::{InHandlePtr, Vertexf32},
@@ -199,10 +201,14 @@ pub unsafe fn vertex_degree_impl(ptr: InHandlePtr, cluster_id: *const c_char) ->
 ///
 /// # Returns
 ///
-/// The cluster label as an `i32` or -1 if the handle is not created
+/// The cluster label as an `i32`, -2 if the dataset has no labels or -1 if the handle is not created
 pub unsafe fn get_cluster_label_impl(ptr: InHandlePtr, cluster_id: *const c_char) -> i32 {
     // If the handle and label exist
     if let Some(handle) = ptr {
+        if handle.tree().is_some() && !handle.has_labels() {
+            debug!("dataset has no labels");
+            return -2;
+        }
         if let Some(labels) = handle.labels() {
             // Get the cluster id as a string
             let cluster_id = helpers::c_char_to_string(cluster_id);
@@ -319,11 +325,14 @@ pub unsafe fn max_lfd_impl(ptr: InHandlePtr) -> f32 {
 ///
 /// # Returns
 ///
-/// An `FFIError` enum
+/// An `FFIError` enum, `FFIError::NoLabels` if the dataset is unlabeled
 pub fn color_clusters_by_entropy_impl(ptr: InHandlePtr, node_visitor: CBFnNodeVisitor) -> FFIError {
     // If the handle and root exist
     if let Some(handle) = ptr {
         if let Some(root) = handle.root() {
+            if !handle.has_labels() {
+                return FFIError::NoLabels;
+            }
             // If the labels exist
             if let Some(labels) = handle.labels() {
                 // Color the clusters by entropy
@@ -346,7 +355,7 @@ pub fn color_clusters_by_entropy_impl(ptr: InHandlePtr, node_visitor: CBFnNodeVi
 /// # Returns
 ///
 /// A `glam::Vec3` representing the entropy color
-fn calc_cluster_entropy_color(cluster: &Vertexf32, labels: &[u8]) -> glam::Vec3 {
+pub(crate) fn calc_cluster_entropy_color(cluster: &Vertexf32, labels: &[u8]) -> glam::Vec3 {
     // Get the indices of the cluster
     let indices = cluster.indices();
     let mut entropy = [0; 2];
@@ -376,7 +385,7 @@ fn calc_cluster_entropy_color(cluster: &Vertexf32, labels: &[u8]) -> glam::Vec3
 /// # Returns
 ///
 /// A `Result` containing the dominant color as a `glam::Vec3` or an error message as a `String`
-fn calc_cluster_dominant_color(
+pub(crate) fn calc_cluster_dominant_color(
     cluster: &Vertexf32,
     labels: &[u8],
     num_unique_labels: usize,
@@ -463,7 +472,7 @@ fn color_helper(root: Option<&Vertexf32>, labels: &[u8], node_visitor: CBFnNodeV
 ///
 /// # Returns
 ///
-/// An `FFIError` enum
+/// An `FFIError` enum, `FFIError::NoLabels` if the dataset is unlabeled
 pub fn color_clusters_by_dominant_label_impl(
     ptr: InHandlePtr,
     node_visitor: CBFnNodeVisitor,
@@ -471,6 +480,9 @@ pub fn color_clusters_by_dominant_label_impl(
     // If the handle and root exist
     if let Some(handle) = ptr {
         if let Some(root) = handle.root() {
+            if !handle.has_labels() {
+                return FFIError::NoLabels;
+            }
             // If the labels exist
             if let Some(labels) = handle.labels() {
                 // Color the clusters by the dominant label
@@ -575,7 +587,7 @@ pub unsafe fn distance_to_other_impl(
         // Return the distance to the other cluster or -1.0 if it doesn't exist
         return if let Ok(node1) = node1 {
             if let Ok(node2) = node2 {
-                node1.distance_to_other(handle.data().unwrap(), node2)
+                handle.tree().unwrap().cluster_distance(node1, node2)
             } else {
                 -1f32
             }
@@ -643,3 +655,179 @@ pub unsafe fn distance_to_other_impl(
 
 //     return FFIError::Ok;
 // }
+
+/// Function to register a distance function from the host under a name
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the name passed to it
+///
+/// # Arguments
+///
+/// * `name` - A pointer to the name to register the function under
+/// * `callback` - A function pointer to the distance function
+/// * `is_expensive` - A boolean indicating if the distance function is expensive
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub unsafe fn register_distance_function_impl(
+    name: *const c_char,
+    callback: Option<CBFnDistance>,
+    is_expensive: bool,
+) -> FFIError {
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return FFIError::NullPointerPassed,
+    };
+    match c_char_to_str(name)
+        .and_then(|name| custom_metrics::register(name, callback, is_expensive))
+    {
+        Ok(()) => FFIError::Ok,
+        Err(e) => e,
+    }
+}
+
+/// Function to remove a distance function registered with `register_distance_function_impl`
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the name passed to it
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub unsafe fn unregister_distance_function_impl(name: *const c_char) -> FFIError {
+    match c_char_to_str(name).and_then(custom_metrics::unregister) {
+        Ok(()) => FFIError::Ok,
+        Err(e) => e,
+    }
+}
+
+/// Function to keep the selected graph of the handle under a name
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the pointers passed to it
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the handle
+/// * `name` - A pointer to the name to keep the graph under
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub unsafe fn save_clam_graph_impl(ptr: InHandlePtr, name: *const c_char) -> FFIError {
+    match (ptr, c_char_to_str(name)) {
+        (Some(handle), Ok(name)) => handle.save_graph_selection(name),
+        (None, _) => FFIError::NullPointerPassed,
+        (_, Err(e)) => e,
+    }
+}
+
+/// Function to switch the handle to a graph kept with `save_clam_graph_impl`
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the pointers passed to it
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the handle
+/// * `name` - A pointer to the name the graph was kept under
+/// * `cluster_selector` - The cluster selector function
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub unsafe fn switch_clam_graph_impl(
+    ptr: InHandlePtr,
+    name: *const c_char,
+    cluster_selector: CBFnNodeVisitor,
+) -> FFIError {
+    match (ptr, c_char_to_str(name)) {
+        (Some(handle), Ok(name)) => handle.switch_graph_selection(name, cluster_selector),
+        (None, _) => FFIError::NullPointerPassed,
+        (_, Err(e)) => e,
+    }
+}
+
+/// Function to forget a graph kept with `save_clam_graph_impl`
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the pointers passed to it
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the handle
+/// * `name` - A pointer to the name the graph was kept under
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub unsafe fn remove_clam_graph_impl(ptr: InHandlePtr, name: *const c_char) -> FFIError {
+    match (ptr, c_char_to_str(name)) {
+        (Some(handle), Ok(name)) => handle.remove_graph_selection(name),
+        (None, _) => FFIError::NullPointerPassed,
+        (_, Err(e)) => e,
+    }
+}
+
+/// Function to create a clam graph from an explicit list of cluster ids
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the pointers passed to it
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the handle
+/// * `ids` - A pointer to `count` cluster ids, each written as `offset-cardinality`
+/// * `count` - The number of cluster ids
+/// * `cluster_selector` - The cluster selector function
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub unsafe fn init_clam_graph_from_ids_impl(
+    ptr: InHandlePtr,
+    ids: *const *const c_char,
+    count: i32,
+    cluster_selector: CBFnNodeVisitor,
+) -> FFIError {
+    let handle = match ptr {
+        Some(handle) => handle,
+        None => return FFIError::NullPointerPassed,
+    };
+    if ids.is_null() || count < 0 {
+        return FFIError::NullPointerPassed;
+    }
+
+    let mut cluster_ids = Vec::with_capacity(count as usize);
+    for &id in std::slice::from_raw_parts(ids, count as usize) {
+        let id = match c_char_to_str(id) {
+            Ok(id) => id,
+            Err(e) => return e,
+        };
+        match helpers::parse_cluster_name(id) {
+            Ok(id) => cluster_ids.push(id),
+            Err(_) => {
+                debug!("invalid cluster id {}", id);
+                return FFIError::InvalidStringPassed;
+            }
+        }
+    }
+
+    handle.init_clam_graph_with(SelectionMode::Ids(cluster_ids), cluster_selector)
+}
+
+pub(crate) unsafe fn c_char_to_str<'a>(name: *const c_char) -> Result<&'a str, FFIError> {
+    if name.is_null() {
+        return Err(FFIError::NullPointerPassed);
+    }
+    CStr::from_ptr(name)
+        .to_str()
+        .map_err(|_| FFIError::InvalidStringPassed)
+}
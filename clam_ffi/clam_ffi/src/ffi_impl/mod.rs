@@ -4,5 +4,6 @@ pub mod cluster_data_wrapper;
 pub mod cluster_ids;
 pub mod cluster_ids_wrapper;
 pub mod lib_impl;
+pub mod partition_criteria_ffi;
 pub mod string_ffi;
 pub mod tree_startup_data_ffi;
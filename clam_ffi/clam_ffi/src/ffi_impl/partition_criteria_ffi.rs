@@ -0,0 +1,75 @@
+use abd_clam::{Cluster, PartitionCriteria, PartitionCriterion};
+
+use crate::utils::types::Vertexf32;
+
+/// The stopping criteria used to partition a tree
+///
+/// Criteria that are zero or negative are not checked, so a zeroed struct partitions the way
+/// `PartitionCriteria::new(true)` does
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct PartitionCriteriaFFI {
+    /// Whether a cluster is partitioned if it passes any criterion. By default it must pass every
+    /// criterion
+    pub check_any: bool,
+    /// The deepest a cluster may be and still be partitioned
+    pub max_depth: i32,
+    /// The smallest radius a cluster may have and still be partitioned
+    pub min_radius: f32,
+    /// The smallest local fractal dimension a cluster may have and still be partitioned
+    pub min_lfd: f32,
+    /// The seed for partitioning the tree and for the graph layout and physics. Negative values use
+    /// no seed
+    pub seed: i64,
+}
+
+impl PartitionCriteriaFFI {
+    /// Function to build the partition criteria
+    ///
+    /// # Arguments
+    ///
+    /// * `min_cardinality` - The smallest cardinality a cluster may have and still be partitioned
+    ///
+    /// # Returns
+    ///
+    /// The `PartitionCriteria` holding every criterion that is set
+    pub fn criteria(&self, min_cardinality: usize) -> PartitionCriteria<f32> {
+        let mut criteria =
+            PartitionCriteria::new(!self.check_any).with_min_cardinality(min_cardinality);
+        if self.max_depth > 0 {
+            criteria = criteria.with_max_depth(self.max_depth as usize);
+        }
+        if self.min_radius > 0.0 {
+            criteria = criteria.with_custom(Box::new(MinRadius(self.min_radius)));
+        }
+        if self.min_lfd > 0.0 {
+            criteria = criteria.with_custom(Box::new(MinLfd(self.min_lfd as f64)));
+        }
+        criteria
+    }
+
+    /// Function to get the seed for partitioning
+    pub fn seed(&self) -> Option<u64> {
+        (self.seed >= 0).then_some(self.seed as u64)
+    }
+}
+
+/// Only partitions clusters with a radius above the threshold
+#[derive(Debug)]
+struct MinRadius(f32);
+
+impl PartitionCriterion<f32> for MinRadius {
+    fn check(&self, c: &Vertexf32) -> bool {
+        c.radius() > self.0
+    }
+}
+
+/// Only partitions clusters with a local fractal dimension of at least the threshold
+#[derive(Debug)]
+struct MinLfd(f64);
+
+impl PartitionCriterion<f32> for MinLfd {
+    fn check(&self, c: &Vertexf32) -> bool {
+        c.lfd() >= self.0
+    }
+}
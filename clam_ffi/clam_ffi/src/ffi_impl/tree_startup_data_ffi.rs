@@ -1,5 +1,12 @@
+use std::path::PathBuf;
+
+use crate::ffi_impl::partition_criteria_ffi::PartitionCriteriaFFI;
 use crate::ffi_impl::string_ffi::StringFFI;
-use crate::utils::distances::DistanceMetric;
+use crate::utils::csv_readers::CsvOptions;
+use crate::utils::data_paths::DataPaths;
+use crate::utils::data_types::DataType;
+use crate::utils::distances::{self, DistanceMetric, MetricParams};
+use crate::utils::error::FFIError;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
@@ -9,4 +16,97 @@ pub struct TreeStartupDataFFI {
     pub cardinality: u32,
     pub is_expensive: bool,
     pub should_load: bool,
+    /// Either a data root or a full path to the features file. Empty uses the default data root
+    pub data_path: StringFFI,
+    /// A full path to the labels file. Empty looks next to the features file
+    pub labels_path: StringFFI,
+    /// The one-based column of a `.csv`/`.tsv` file holding the labels. Negative values count from
+    /// the end, and zero is the last column
+    pub label_column: i32,
+    /// The field delimiter of a `.csv`/`.tsv` file. Zero picks one from the file extension
+    pub delimiter: u8,
+    /// Whether the first row of a `.csv`/`.tsv` file is data. By default it is a header and skipped
+    pub no_header: bool,
+    /// Whether the dataset has no labels. If set, no labels file or label column is read
+    pub features_only: bool,
+    /// The element type of a saved tree. Built trees take the element type of their `.npy` file
+    pub data_type: DataType,
+    /// The `p` of the Minkowski metric. Only read when `distance_metric` is `Minkowski`, and zero
+    /// is 2. Each tree keeps the `p` it was built or loaded with
+    pub minkowski_p: f32,
+    /// The name of a registered distance function. Only read when `distance_metric` is `Custom`.
+    /// Empty uses the most recently registered function
+    pub custom_metric: StringFFI,
+    /// The stopping criteria and seed for partitioning. `cardinality` is the minimum cardinality
+    pub partition_criteria: PartitionCriteriaFFI,
+}
+
+impl TreeStartupDataFFI {
+    /// Function to resolve the dataset files named by the startup data
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the resolved paths or an `FFIError` naming the file that is missing
+    pub fn data_paths(&self) -> Result<DataPaths, FFIError> {
+        let data_name = self.data_name.as_string()?;
+        let data_path = optional_path(&self.data_path)?;
+        let labels_path = optional_path(&self.labels_path)?;
+
+        DataPaths::resolve(
+            &data_name,
+            data_path.as_deref(),
+            labels_path.as_deref(),
+            !self.features_only,
+        )
+    }
+
+    /// Function to get the parameters of the distance metric from the startup data
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is an `FFIError` if the name of the custom metric is not valid UTF-8
+    pub fn metric_params(&self) -> Result<MetricParams, FFIError> {
+        let minkowski_p = if self.minkowski_p == 0.0 {
+            distances::DEFAULT_MINKOWSKI_P
+        } else {
+            self.minkowski_p
+        };
+        let custom_metric = if self.distance_metric == DistanceMetric::Custom
+            && !(self.custom_metric.is_empty() || self.custom_metric.len == 0)
+        {
+            Some(self.custom_metric.as_string()?)
+        } else {
+            None
+        };
+        Ok(MetricParams {
+            minkowski_p,
+            custom_metric,
+        })
+    }
+
+    /// Function to get the layout of a delimited features file from the startup data
+    ///
+    /// The fields are encoded so that a zeroed struct reads a file the way `CsvOptions::default`
+    /// does
+    pub fn csv_options(&self) -> CsvOptions {
+        let label_column = match self.label_column {
+            0 => -1,
+            column if column > 0 => column - 1,
+            column => column,
+        };
+
+        CsvOptions {
+            delimiter: (self.delimiter != 0).then_some(self.delimiter),
+            has_header: !self.no_header,
+            label_column: (!self.features_only).then_some(label_column),
+        }
+    }
+}
+
+fn optional_path(path: &StringFFI) -> Result<Option<PathBuf>, FFIError> {
+    if path.is_empty() || path.len == 0 {
+        Ok(None)
+    } else {
+        path.as_string().map(|path| Some(PathBuf::from(path)))
+    }
 }
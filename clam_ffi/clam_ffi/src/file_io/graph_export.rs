@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use abd_clam::Cluster;
+
+use crate::debug;
+use crate::file_io::graph_layout::GraphLayout;
+use crate::utils::clam_tree::ClamTree;
+use crate::utils::error::FFIError;
+use crate::utils::helpers;
+use crate::utils::types::{Graphf32, Vertexf32};
+
+/// The file formats a clam graph can be exported to
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum GraphFormat {
+    /// GraphML, read by NetworkX, Gephi and yEd
+    GraphMl,
+    /// GEXF 1.3, read by Gephi and NetworkX
+    Gexf,
+    /// Graphviz DOT
+    Dot,
+}
+
+impl GraphFormat {
+    /// Function to get a format from the extension of a path, such as `.graphml`, `.gexf` or `.dot`
+    pub fn from_path(path: &Path) -> Result<Self, FFIError> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("graphml") => Ok(GraphFormat::GraphMl),
+            Some("gexf") => Ok(GraphFormat::Gexf),
+            Some("dot") | Some("gv") => Ok(GraphFormat::Dot),
+            _ => Err(FFIError::UnsupportedGraphFormat),
+        }
+    }
+}
+
+/// A cluster of the graph with the properties that are exported
+pub struct ExportVertex {
+    pub id: String,
+    pub cardinality: usize,
+    pub radius: f32,
+    pub lfd: f64,
+    pub depth: usize,
+    /// The most common label of the instances in the cluster, if the dataset has labels
+    pub dominant_label: Option<u8>,
+    /// The position of the cluster in the force-directed layout, if there is one
+    pub position: Option<glam::Vec3>,
+}
+
+/// An edge of the graph, or a virtual spring the force-directed layout added between components
+pub struct ExportEdge {
+    pub source: String,
+    pub target: String,
+    pub distance: f32,
+    pub is_real: bool,
+}
+
+/// The vertices and edges of a clam graph, ready to be written to a file
+pub struct GraphExport {
+    pub vertices: Vec<ExportVertex>,
+    pub edges: Vec<ExportEdge>,
+}
+
+impl GraphExport {
+    /// Function to collect the vertices and edges of a clam graph
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The tree the graph was selected from
+    /// * `graph` - The graph to export
+    /// * `labels` - The labels of the dataset, if it has any
+    /// * `layout` - The force-directed layout of the graph, if there is one. Its positions are
+    ///   added to the vertices and its virtual springs are added as edges
+    pub fn new(
+        tree: &ClamTree,
+        graph: &Graphf32,
+        labels: Option<&[u8]>,
+        layout: Option<&GraphLayout>,
+    ) -> Self {
+        let positions: HashMap<&str, glam::Vec3> = layout
+            .iter()
+            .flat_map(|layout| layout.positions.iter())
+            .map(|(name, position)| (name.as_str(), *position))
+            .collect();
+        let position_of = |id: &str| positions.get(id).copied();
+
+        let mut vertices: Vec<ExportVertex> = graph
+            .ordered_clusters()
+            .iter()
+            .map(|cluster| {
+                let id = cluster.name();
+                ExportVertex {
+                    position: position_of(&id),
+                    id,
+                    cardinality: cluster.cardinality(),
+                    radius: cluster.radius(),
+                    lfd: cluster.lfd(),
+                    depth: cluster.depth(),
+                    dominant_label: labels.and_then(|labels| dominant_label(cluster, labels)),
+                }
+            })
+            .collect();
+        vertices.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let mut edges: Vec<ExportEdge> = graph
+            .edges()
+            .iter()
+            .map(|edge| ExportEdge {
+                source: edge.left().name(),
+                target: edge.right().name(),
+                distance: edge.distance(),
+                is_real: true,
+            })
+            .collect();
+
+        if let Some(layout) = layout {
+            for spring in layout.springs.iter().filter(|spring| !spring.is_real) {
+                let (id1, id2) = spring.get_node_ids();
+                let cluster = |id: &str| {
+                    helpers::parse_cluster_name(id)
+                        .ok()
+                        .and_then(|(offset, cardinality)| tree.get_cluster(offset, cardinality))
+                };
+                if let (Some(left), Some(right)) = (cluster(id1), cluster(id2)) {
+                    edges.push(ExportEdge {
+                        source: id1.clone(),
+                        target: id2.clone(),
+                        distance: tree.cluster_distance(left, right),
+                        is_real: false,
+                    });
+                }
+            }
+        }
+
+        GraphExport { vertices, edges }
+    }
+
+    /// Function to write the graph to a file
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is `FFIError::SaveFailed` if the file could not be written
+    pub fn save(&self, path: &Path, format: GraphFormat) -> Result<(), FFIError> {
+        let contents = match format {
+            GraphFormat::GraphMl => self.to_graphml(),
+            GraphFormat::Gexf => self.to_gexf(),
+            GraphFormat::Dot => self.to_dot(),
+        };
+
+        fs::write(path, contents).map_err(|e| {
+            debug!("{:?}", e);
+            FFIError::SaveFailed
+        })
+    }
+
+    /// Function to write the graph as GraphML
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        );
+        let keys = [
+            ("cardinality", "node", "long"),
+            ("radius", "node", "double"),
+            ("lfd", "node", "double"),
+            ("depth", "node", "int"),
+            ("dominant_label", "node", "int"),
+            ("x", "node", "float"),
+            ("y", "node", "float"),
+            ("z", "node", "float"),
+            ("distance", "edge", "double"),
+            ("is_real", "edge", "boolean"),
+        ];
+        for (name, domain, kind) in keys {
+            let _ = writeln!(
+                out,
+                "  <key id=\"{name}\" for=\"{domain}\" attr.name=\"{name}\" attr.type=\"{kind}\"/>"
+            );
+        }
+
+        out.push_str("  <graph id=\"clam\" edgedefault=\"undirected\">\n");
+        for vertex in &self.vertices {
+            let _ = writeln!(out, "    <node id=\"{}\">", xml_escape(&vertex.id));
+            for (key, value) in vertex.attributes() {
+                let _ = writeln!(out, "      <data key=\"{key}\">{value}</data>");
+            }
+            out.push_str("    </node>\n");
+        }
+        for edge in &self.edges {
+            let _ = writeln!(
+                out,
+                "    <edge source=\"{}\" target=\"{}\">\n      \
+                 <data key=\"distance\">{}</data>\n      \
+                 <data key=\"is_real\">{}</data>\n    \
+                 </edge>",
+                xml_escape(&edge.source),
+                xml_escape(&edge.target),
+                edge.distance,
+                edge.is_real
+            );
+        }
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    /// Function to write the graph as GEXF, with the layout positions as `viz:position`
+    pub fn to_gexf(&self) -> String {
+        let mut out = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <gexf xmlns=\"http://gexf.net/1.3\" xmlns:viz=\"http://gexf.net/1.3/viz\" version=\"1.3\">\n  \
+             <graph defaultedgetype=\"undirected\" mode=\"static\">\n    \
+             <attributes class=\"node\">\n      \
+             <attribute id=\"cardinality\" title=\"cardinality\" type=\"long\"/>\n      \
+             <attribute id=\"radius\" title=\"radius\" type=\"double\"/>\n      \
+             <attribute id=\"lfd\" title=\"lfd\" type=\"double\"/>\n      \
+             <attribute id=\"depth\" title=\"depth\" type=\"integer\"/>\n      \
+             <attribute id=\"dominant_label\" title=\"dominant_label\" type=\"integer\"/>\n    \
+             </attributes>\n    \
+             <attributes class=\"edge\">\n      \
+             <attribute id=\"distance\" title=\"distance\" type=\"double\"/>\n      \
+             <attribute id=\"is_real\" title=\"is_real\" type=\"boolean\"/>\n    \
+             </attributes>\n",
+        );
+
+        out.push_str("    <nodes>\n");
+        for vertex in &self.vertices {
+            let id = xml_escape(&vertex.id);
+            let _ = writeln!(out, "      <node id=\"{id}\" label=\"{id}\">");
+            out.push_str("        <attvalues>\n");
+            for (key, value) in vertex.attributes() {
+                if !matches!(key, "x" | "y" | "z") {
+                    let _ = writeln!(out, "          <attvalue for=\"{key}\" value=\"{value}\"/>");
+                }
+            }
+            out.push_str("        </attvalues>\n");
+            if let Some(position) = vertex.position {
+                let _ = writeln!(
+                    out,
+                    "        <viz:position x=\"{}\" y=\"{}\" z=\"{}\"/>",
+                    position.x, position.y, position.z
+                );
+            }
+            out.push_str("      </node>\n");
+        }
+        out.push_str("    </nodes>\n    <edges>\n");
+        for (i, edge) in self.edges.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "      <edge id=\"{}\" source=\"{}\" target=\"{}\">\n        \
+                 <attvalues>\n          \
+                 <attvalue for=\"distance\" value=\"{}\"/>\n          \
+                 <attvalue for=\"is_real\" value=\"{}\"/>\n        \
+                 </attvalues>\n      \
+                 </edge>",
+                i,
+                xml_escape(&edge.source),
+                xml_escape(&edge.target),
+                edge.distance,
+                edge.is_real
+            );
+        }
+        out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+        out
+    }
+
+    /// Function to write the graph as Graphviz DOT
+    ///
+    /// Layout positions are written as `pos` and virtual edges are dashed
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("graph clam {\n");
+        for vertex in &self.vertices {
+            let mut attributes: Vec<String> = vertex
+                .attributes()
+                .into_iter()
+                .filter(|(key, _)| !matches!(*key, "x" | "y" | "z"))
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect();
+            if let Some(position) = vertex.position {
+                attributes.push(format!(
+                    "pos=\"{},{},{}\"",
+                    position.x, position.y, position.z
+                ));
+            }
+            let _ = writeln!(
+                out,
+                "  \"{}\" [{}];",
+                dot_escape(&vertex.id),
+                attributes.join(", ")
+            );
+        }
+        for edge in &self.edges {
+            let style = if edge.is_real { "" } else { ", style=dashed" };
+            let _ = writeln!(
+                out,
+                "  \"{}\" -- \"{}\" [distance={}, is_real={}{}];",
+                dot_escape(&edge.source),
+                dot_escape(&edge.target),
+                edge.distance,
+                edge.is_real,
+                style
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl ExportVertex {
+    /// Function to list the attributes of the vertex that have a value, by name
+    fn attributes(&self) -> Vec<(&'static str, String)> {
+        let mut attributes = vec![
+            ("cardinality", self.cardinality.to_string()),
+            ("radius", self.radius.to_string()),
+            ("lfd", self.lfd.to_string()),
+            ("depth", self.depth.to_string()),
+        ];
+        if let Some(label) = self.dominant_label {
+            attributes.push(("dominant_label", label.to_string()));
+        }
+        if let Some(position) = self.position {
+            attributes.push(("x", position.x.to_string()));
+            attributes.push(("y", position.y.to_string()));
+            attributes.push(("z", position.z.to_string()));
+        }
+        attributes
+    }
+}
+
+/// Function to find the most common label of the instances in a cluster
+fn dominant_label(cluster: &Vertexf32, labels: &[u8]) -> Option<u8> {
+    let mut counts = [0usize; 256];
+    cluster
+        .indices()
+        .for_each(|i| counts[labels[i] as usize] += 1);
+    counts
+        .iter()
+        .enumerate()
+        .filter(|&(_, &count)| count > 0)
+        .max_by_key(|&(_, &count)| count)
+        .map(|(label, _)| label as u8)
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_graph() -> GraphExport {
+        GraphExport {
+            vertices: vec![
+                ExportVertex {
+                    id: "0-10".to_string(),
+                    cardinality: 10,
+                    radius: 1.5,
+                    lfd: 2.0,
+                    depth: 1,
+                    dominant_label: Some(3),
+                    position: Some(glam::Vec3::new(1.0, 2.0, 3.0)),
+                },
+                ExportVertex {
+                    id: "10-5".to_string(),
+                    cardinality: 5,
+                    radius: 0.5,
+                    lfd: 1.0,
+                    depth: 2,
+                    dominant_label: None,
+                    position: None,
+                },
+            ],
+            edges: vec![ExportEdge {
+                source: "0-10".to_string(),
+                target: "10-5".to_string(),
+                distance: 0.25,
+                is_real: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_graph_export_formats() {
+        let graph = test_graph();
+
+        let graphml = graph.to_graphml();
+        assert_eq!(graphml.matches("<node ").count(), 2);
+        assert!(graphml.contains("<data key=\"dominant_label\">3</data>"));
+        assert!(graphml.contains("<edge source=\"0-10\" target=\"10-5\">"));
+        assert!(graphml.contains("<data key=\"is_real\">false</data>"));
+
+        let gexf = graph.to_gexf();
+        assert_eq!(gexf.matches("<node ").count(), 2);
+        assert_eq!(gexf.matches("<viz:position ").count(), 1);
+        assert!(gexf.contains("<attvalue for=\"distance\" value=\"0.25\"/>"));
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("\"0-10\" [cardinality=10, radius=1.5, lfd=2, depth=1, dominant_label=3, pos=\"1,2,3\"];"));
+        assert!(dot.contains("\"0-10\" -- \"10-5\" [distance=0.25, is_real=false, style=dashed];"));
+
+        assert_eq!(
+            GraphFormat::from_path(Path::new("graph.gexf")),
+            Ok(GraphFormat::Gexf)
+        );
+        assert_eq!(
+            GraphFormat::from_path(Path::new("graph.txt")),
+            Err(FFIError::UnsupportedGraphFormat)
+        );
+    }
+}
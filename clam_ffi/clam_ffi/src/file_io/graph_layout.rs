@@ -0,0 +1,250 @@
+use std::fs;
+use std::path::Path;
+
+use crate::debug;
+use crate::graph::force_directed_graph::ForceDirectedGraph;
+use crate::graph::graph_selection::SelectionMode;
+use crate::graph::spring::Spring;
+use crate::utils::error::FFIError;
+use crate::utils::scoring_functions;
+
+/// The first line of every layout file
+const LAYOUT_HEADER: &str = "clam_graph_layout";
+
+/// The version of the layout format written by `GraphLayout::save`
+pub const LAYOUT_VERSION: u32 = 2;
+
+/// The positions and springs of a force-directed layout and the graph selection it was run on
+///
+/// Layouts are saved as text. The header and version come first, then the selection mode, then one
+/// line per cluster and one line per spring. Version 1 files have a scoring function and min depth
+/// line in place of the selection mode.
+#[derive(Clone)]
+pub struct GraphLayout {
+    pub selection: SelectionMode,
+    pub positions: Vec<(String, glam::Vec3)>,
+    pub springs: Vec<Spring>,
+}
+
+impl GraphLayout {
+    /// Function to copy the current positions and springs out of a force-directed graph
+    ///
+    /// # Arguments
+    ///
+    /// * `force_directed_graph` - The force-directed graph to copy
+    /// * `selection` - How the clusters of the clam graph were chosen
+    pub fn capture(force_directed_graph: &ForceDirectedGraph, selection: SelectionMode) -> Self {
+        let springs = force_directed_graph
+            .edges()
+            .iter()
+            .map(|spring| {
+                let (id1, id2) = spring.get_node_ids();
+                Spring::new(spring.nat_len(), id1.clone(), id2.clone(), spring.is_real)
+            })
+            .collect();
+
+        GraphLayout {
+            selection,
+            positions: force_directed_graph.positions(),
+            springs,
+        }
+    }
+
+    /// Function to save the layout to a file
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is an `FFIError` if the file could not be written
+    pub fn save(&self, path: &Path) -> Result<(), FFIError> {
+        let mut contents = format!(
+            "{} {}\nselection {}\nclusters {}\n",
+            LAYOUT_HEADER,
+            LAYOUT_VERSION,
+            self.selection,
+            self.positions.len()
+        );
+        for (id, position) in &self.positions {
+            contents.push_str(&format!(
+                "{} {} {} {}\n",
+                id, position.x, position.y, position.z
+            ));
+        }
+        contents.push_str(&format!("springs {}\n", self.springs.len()));
+        for spring in &self.springs {
+            let (id1, id2) = spring.get_node_ids();
+            contents.push_str(&format!(
+                "{} {} {} {}\n",
+                id1,
+                id2,
+                spring.nat_len(),
+                spring.is_real as i32
+            ));
+        }
+
+        fs::write(path, contents).map_err(|e| {
+            debug!("{:?}", e);
+            FFIError::SaveFailed
+        })
+    }
+
+    /// Function to load a layout saved with `GraphLayout::save`
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the layout or `FFIError::LoadLayoutFailed` if the file could not be
+    /// read, is from a newer version or is malformed
+    pub fn load(path: &Path) -> Result<Self, FFIError> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            debug!("{:?}", e);
+            FFIError::LoadLayoutFailed
+        })?;
+
+        Self::parse(&contents).map_err(|e| {
+            debug!("invalid layout file {}: {}", path.display(), e);
+            FFIError::LoadLayoutFailed
+        })
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut lines = contents.lines();
+
+        let version: u32 = parse_field(lines.next(), LAYOUT_HEADER)?;
+        if version > LAYOUT_VERSION {
+            return Err(format!("unsupported version {}", version));
+        }
+
+        let selection = if version < 2 {
+            let scoring_function: String = parse_field(lines.next(), "scoring_function")?;
+            let scoring_function = scoring_functions::string_to_enum(&scoring_function)
+                .map_err(|_| format!("unknown scoring function {}", scoring_function))?;
+            SelectionMode::Scorer(scoring_function, parse_field(lines.next(), "min_depth")?)
+        } else {
+            let line = lines.next().ok_or("unexpected end of file")?;
+            let mode = line
+                .strip_prefix("selection ")
+                .ok_or_else(|| format!("expected selection but found '{}'", line))?;
+            SelectionMode::parse(mode)?
+        };
+
+        let num_clusters: usize = parse_field(lines.next(), "clusters")?;
+        let mut positions = Vec::with_capacity(num_clusters);
+        for _ in 0..num_clusters {
+            let fields = split_fields(lines.next(), 4)?;
+            let position = glam::Vec3::new(
+                parse_value(fields[1])?,
+                parse_value(fields[2])?,
+                parse_value(fields[3])?,
+            );
+            positions.push((fields[0].to_string(), position));
+        }
+
+        let num_springs: usize = parse_field(lines.next(), "springs")?;
+        let mut springs = Vec::with_capacity(num_springs);
+        for _ in 0..num_springs {
+            let fields = split_fields(lines.next(), 4)?;
+            springs.push(Spring::new(
+                parse_value(fields[2])?,
+                fields[0].to_string(),
+                fields[1].to_string(),
+                parse_value::<i32>(fields[3])? != 0,
+            ));
+        }
+
+        Ok(GraphLayout {
+            selection,
+            positions,
+            springs,
+        })
+    }
+}
+
+fn split_fields(line: Option<&str>, count: usize) -> Result<Vec<&str>, String> {
+    let line = line.ok_or("unexpected end of file")?;
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    if fields.len() != count {
+        return Err(format!("expected {} fields in line '{}'", count, line));
+    }
+    Ok(fields)
+}
+
+fn parse_field<T: std::str::FromStr>(line: Option<&str>, name: &str) -> Result<T, String> {
+    let fields = split_fields(line, 2)?;
+    if fields[0] != name {
+        return Err(format!("expected {} but found {}", name, fields[0]));
+    }
+    parse_value(fields[1])
+}
+
+fn parse_value<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::scoring_functions::ScoringFunction;
+
+    #[test]
+    fn test_layout_round_trip() {
+        let layout = GraphLayout {
+            selection: SelectionMode::Scorer(ScoringFunction::LrEuclideanCc, 4),
+            positions: vec![
+                ("0-10".to_string(), glam::Vec3::new(1.5, -2.25, 0.1)),
+                ("10-5".to_string(), glam::Vec3::new(0.0, 100.0, 3.3333333)),
+            ],
+            springs: vec![Spring::new(
+                0.75,
+                "0-10".to_string(),
+                "10-5".to_string(),
+                true,
+            )],
+        };
+
+        let path = std::env::temp_dir().join("clam_ffi_test_layout_round_trip.layout");
+        layout.save(&path).unwrap();
+        let loaded = GraphLayout::load(&path).unwrap();
+
+        assert_eq!(loaded.selection, layout.selection);
+        assert_eq!(loaded.positions, layout.positions);
+        assert_eq!(loaded.springs.len(), 1);
+        assert_eq!(
+            loaded.springs[0].get_node_ids(),
+            layout.springs[0].get_node_ids()
+        );
+        assert_eq!(loaded.springs[0].nat_len(), 0.75);
+        assert!(loaded.springs[0].is_real);
+    }
+
+    #[test]
+    fn test_layout_version_1() {
+        let path = std::env::temp_dir().join("clam_ffi_test_layout_version_1.layout");
+        fs::write(
+            &path,
+            format!(
+                "{} 1\nscoring_function lr_euclidean_cc\nmin_depth 4\nclusters 1\n0-10 1 2 3\nsprings 0\n",
+                LAYOUT_HEADER
+            ),
+        )
+        .unwrap();
+
+        let loaded = GraphLayout::load(&path).unwrap();
+        assert_eq!(
+            loaded.selection,
+            SelectionMode::Scorer(ScoringFunction::LrEuclideanCc, 4)
+        );
+        assert_eq!(loaded.positions.len(), 1);
+    }
+
+    #[test]
+    fn test_layout_newer_version() {
+        let path = std::env::temp_dir().join("clam_ffi_test_layout_newer_version.layout");
+        fs::write(&path, format!("{} {}\n", LAYOUT_HEADER, LAYOUT_VERSION + 1)).unwrap();
+
+        assert_eq!(
+            GraphLayout::load(&path).err(),
+            Some(FFIError::LoadLayoutFailed)
+        );
+    }
+}
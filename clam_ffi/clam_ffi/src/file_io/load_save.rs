@@ -1,7 +1,12 @@
 use crate::debug;
+use crate::file_io::graph_export::GraphFormat;
+use crate::file_io::mesh_export::{MeshExportOptions, MeshFormat};
+use crate::file_io::tree_render::{save_tree_render, ImageFormat, TreeRenderOptions};
+use crate::handle::handle::Handle;
 use crate::utils::error::FFIError;
 use crate::utils::helpers;
 use crate::utils::types::InHandlePtr;
+use crate::CBFnNodeVisitor;
 use std::path::Path;
 
 // pub fn load_single_f32(
@@ -27,19 +32,181 @@ pub unsafe fn save_cakes_single_impl(
             }
         };
 
-        if let Some(tree) = handle.tree() {
-            let p = Path::new(&path);
-            match tree.save(p) {
-                Ok(_) => FFIError::Ok,
-                Err(e) => {
-                    debug!("save cakes single error: {:?}", e);
-                    FFIError::SaveFailed
-                }
-            }
-        } else {
-            FFIError::HandleInitFailed
-        }
+        save_tree(handle, Path::new(&path))
     } else {
         FFIError::InvalidStringPassed
     };
 }
+
+pub unsafe fn save_graph_layout_impl(
+    ptr: InHandlePtr,
+    file_name: *const u8,
+    name_len: i32,
+) -> FFIError {
+    if let Some(handle) = ptr {
+        match helpers::csharp_to_rust_utf8(file_name, name_len) {
+            Ok(path) => handle.save_graph_layout(Path::new(&path)),
+            Err(e) => {
+                debug!("save graph layout error: {:?}", e);
+                e
+            }
+        }
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+pub unsafe fn load_graph_layout_impl(
+    ptr: InHandlePtr,
+    file_name: *const u8,
+    name_len: i32,
+    cluster_selector: CBFnNodeVisitor,
+) -> FFIError {
+    if let Some(handle) = ptr {
+        match helpers::csharp_to_rust_utf8(file_name, name_len) {
+            Ok(path) => handle.load_graph_layout(Path::new(&path), cluster_selector),
+            Err(e) => {
+                debug!("load graph layout error: {:?}", e);
+                e
+            }
+        }
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+/// Function to export the selected graph of a handle to GraphML, GEXF or DOT
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the pointers passed to it
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the handle
+/// * `file_name` - The path to export the graph to
+/// * `name_len` - The length of the path
+/// * `format` - The file format to write
+///
+/// # Returns
+///
+/// An `FFIError` indicating if the graph was exported successfully or not
+pub unsafe fn export_graph_impl(
+    ptr: InHandlePtr,
+    file_name: *const u8,
+    name_len: i32,
+    format: GraphFormat,
+) -> FFIError {
+    if let Some(handle) = ptr {
+        match helpers::csharp_to_rust_utf8(file_name, name_len) {
+            Ok(path) => handle.export_graph(Path::new(&path), format),
+            Err(e) => {
+                debug!("export graph error: {:?}", e);
+                e
+            }
+        }
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+/// Function to export a layout of the tree or the selected graph of a handle as a mesh
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the pointers passed to it
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the handle
+/// * `file_name` - The path to export the mesh to
+/// * `name_len` - The length of the path
+/// * `format` - The file format to write
+/// * `options` - The layout, coloring and sizing of the clusters
+///
+/// # Returns
+///
+/// An `FFIError` indicating if the mesh was exported successfully or not
+pub unsafe fn export_mesh_impl(
+    ptr: InHandlePtr,
+    file_name: *const u8,
+    name_len: i32,
+    format: MeshFormat,
+    options: Option<&MeshExportOptions>,
+) -> FFIError {
+    if let (Some(handle), Some(options)) = (ptr, options) {
+        match helpers::csharp_to_rust_utf8(file_name, name_len) {
+            Ok(path) => handle.export_mesh(Path::new(&path), format, options),
+            Err(e) => {
+                debug!("export mesh error: {:?}", e);
+                e
+            }
+        }
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+/// Function to render the Reingold-Tilford layout of the tree of a handle to an svg or png
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the pointers passed to it
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the handle
+/// * `file_name` - The path to render the tree to
+/// * `name_len` - The length of the path
+/// * `format` - The image format to write
+/// * `options` - The coloring, labels, depth and sizes of the drawing
+///
+/// # Returns
+///
+/// An `FFIError` indicating if the tree was rendered successfully or not
+pub unsafe fn render_tree_impl(
+    ptr: InHandlePtr,
+    file_name: *const u8,
+    name_len: i32,
+    format: ImageFormat,
+    options: Option<&TreeRenderOptions>,
+) -> FFIError {
+    if let (Some(handle), Some(options)) = (ptr, options) {
+        match helpers::csharp_to_rust_utf8(file_name, name_len) {
+            Ok(path) => match save_tree_render(handle, Path::new(&path), format, options) {
+                Ok(()) => FFIError::Ok,
+                Err(e) => e,
+            },
+            Err(e) => {
+                debug!("render tree error: {:?}", e);
+                e
+            }
+        }
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+/// Function to save the tree of a handle so it can be loaded with `load_cakes_struct`
+///
+/// # Arguments
+///
+/// * `handle` - The handle holding the tree
+/// * `path` - The path to save the tree to
+///
+/// # Returns
+///
+/// An `FFIError` indicating if the tree was saved successfully or not
+pub fn save_tree(handle: &Handle, path: &Path) -> FFIError {
+    if let Some(tree) = handle.tree() {
+        match tree.save(path) {
+            Ok(_) => FFIError::Ok,
+            Err(e) => {
+                debug!("save cakes single error: {:?}", e);
+                FFIError::SaveFailed
+            }
+        }
+    } else {
+        FFIError::HandleInitFailed
+    }
+}
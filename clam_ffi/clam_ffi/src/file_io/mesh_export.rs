@@ -0,0 +1,627 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use abd_clam::Cluster;
+
+use crate::debug;
+use crate::file_io::graph_layout::GraphLayout;
+use crate::handle::handle::Handle;
+use crate::tree_layout::reingold_tilford;
+use crate::utils::coloring::ClusterColoring;
+use crate::utils::error::FFIError;
+use crate::utils::types::Vertexf32;
+
+/// The number of rings and segments of the sphere drawn for each cluster
+const SPHERE_RINGS: u32 = 8;
+const SPHERE_SEGMENTS: u32 = 12;
+
+/// The colors of edges of the graph and of virtual springs added between its components
+const EDGE_COLOR: glam::Vec3 = glam::Vec3::new(0.6, 0.6, 0.6);
+const VIRTUAL_EDGE_COLOR: glam::Vec3 = glam::Vec3::new(0.3, 0.3, 0.3);
+
+/// The file formats a layout can be exported to as a mesh
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum MeshFormat {
+    /// A self-contained glTF 2.0 scene with the buffers embedded in the file
+    Gltf,
+    /// Wavefront OBJ with vertex colors
+    Obj,
+    /// ASCII PLY with vertex and edge colors
+    Ply,
+}
+
+impl MeshFormat {
+    /// Function to get a format from the extension of a path, such as `.gltf`, `.obj` or `.ply`
+    pub fn from_path(path: &Path) -> Result<Self, FFIError> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gltf") => Ok(MeshFormat::Gltf),
+            Some("obj") => Ok(MeshFormat::Obj),
+            Some("ply") => Ok(MeshFormat::Ply),
+            _ => Err(FFIError::UnsupportedMeshFormat),
+        }
+    }
+}
+
+/// The layout the positions of the clusters are taken from
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum MeshLayout {
+    /// The force-directed layout of the selected graph, with its edges and virtual springs
+    Graph,
+    /// The Reingold-Tilford layout of the tree, with an edge from every parent to its children
+    Tree,
+}
+
+/// What the size of the sphere of a cluster shows
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum NodeSizing {
+    /// The radius of the cluster relative to the radius of the root
+    Radius,
+    /// The cube root of the cardinality of the cluster relative to the cardinality of the root, so
+    /// the volume of the sphere grows with the cardinality
+    Cardinality,
+}
+
+impl NodeSizing {
+    /// Function to get a sizing from its name, `radius` or `cardinality`
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "radius" => Ok(NodeSizing::Radius),
+            "cardinality" => Ok(NodeSizing::Cardinality),
+            _ => Err(format!("unknown node sizing {}", name)),
+        }
+    }
+}
+
+/// The options of a mesh export
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct MeshExportOptions {
+    pub layout: MeshLayout,
+    pub coloring: ClusterColoring,
+    pub sizing: NodeSizing,
+    /// The radius of the sphere of the root, which every other sphere is scaled from
+    pub node_scale: f32,
+    /// The deepest clusters drawn in the tree layout. Negative values draw the whole tree
+    pub max_depth: i32,
+}
+
+/// A cluster drawn as a sphere
+pub struct MeshSphere {
+    pub center: glam::Vec3,
+    pub radius: f32,
+    pub color: glam::Vec3,
+}
+
+/// An edge drawn as a line segment
+pub struct MeshLine {
+    pub start: glam::Vec3,
+    pub end: glam::Vec3,
+    pub color: glam::Vec3,
+}
+
+/// The spheres and lines of an exported layout
+pub struct MeshScene {
+    pub spheres: Vec<MeshSphere>,
+    pub lines: Vec<MeshLine>,
+}
+
+/// The triangles of the spheres of a scene as flat vertex and index lists
+struct Triangles {
+    positions: Vec<glam::Vec3>,
+    normals: Vec<glam::Vec3>,
+    colors: Vec<glam::Vec3>,
+    indices: Vec<u32>,
+}
+
+impl MeshScene {
+    /// Function to build the scene of a layout of the tree or the selected graph of a handle
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The handle holding the tree and graph
+    /// * `options` - The layout, coloring and sizing of the scene
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the scene, `FFIError::NoGraphLayout` if the graph layout was asked for
+    /// but the physics has not run on the graph, or another `FFIError` if the clusters could not be
+    /// colored
+    pub fn new(handle: &Handle, options: &MeshExportOptions) -> Result<Self, FFIError> {
+        let tree = handle.tree().ok_or(FFIError::HandleInitFailed)?;
+
+        match options.layout {
+            MeshLayout::Graph => {
+                let layout = handle.graph_layout().ok_or(FFIError::NoGraphLayout)?;
+                Self::from_layout(handle, &layout, options)
+            }
+            MeshLayout::Tree => {
+                let max_depth = if options.max_depth < 0 {
+                    tree.depth() as i32
+                } else {
+                    options.max_depth
+                };
+                let layout = reingold_tilford::positions(tree.root(), max_depth);
+                let edges: Vec<_> = layout
+                    .edges
+                    .into_iter()
+                    .map(|(parent, child)| (parent, child, true))
+                    .collect();
+                Self::from_positions(handle, &layout.positions, &edges, options)
+            }
+        }
+    }
+
+    /// Function to build the scene of a force-directed layout of a graph selected from the tree of
+    /// a handle
+    ///
+    /// # Arguments
+    ///
+    /// * `handle` - The handle holding the tree
+    /// * `layout` - The layout to draw
+    /// * `options` - The coloring and sizing of the scene
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the scene or an `FFIError` if the clusters could not be colored
+    pub fn from_layout(
+        handle: &Handle,
+        layout: &GraphLayout,
+        options: &MeshExportOptions,
+    ) -> Result<Self, FFIError> {
+        let edges: Vec<_> = layout
+            .springs
+            .iter()
+            .map(|spring| {
+                let (id1, id2) = spring.get_node_ids();
+                (id1.clone(), id2.clone(), spring.is_real)
+            })
+            .collect();
+        Self::from_positions(handle, &layout.positions, &edges, options)
+    }
+
+    /// Function to build a scene from the positions of clusters and the edges between them, given
+    /// by cluster name and whether the edge is real
+    fn from_positions(
+        handle: &Handle,
+        positions: &[(String, glam::Vec3)],
+        edges: &[(String, String, bool)],
+        options: &MeshExportOptions,
+    ) -> Result<Self, FFIError> {
+        let root = handle.root().ok_or(FFIError::HandleInitFailed)?;
+        let clusters = positions
+            .iter()
+            .map(|(id, _)| handle.get_cluster_from_string(id.clone()))
+            .collect::<Result<Vec<&Vertexf32>, _>>()?;
+        let colors = handle.cluster_colors(&clusters, options.coloring)?;
+
+        let spheres = clusters
+            .iter()
+            .zip(positions.iter())
+            .zip(colors)
+            .map(|((cluster, (_, center)), color)| MeshSphere {
+                center: *center,
+                radius: options.node_scale * relative_size(cluster, root, options.sizing),
+                color,
+            })
+            .collect();
+
+        let positions: HashMap<&str, glam::Vec3> = positions
+            .iter()
+            .map(|(name, position)| (name.as_str(), *position))
+            .collect();
+        let position_of = |id: &str| positions.get(id).copied();
+        let lines = edges
+            .iter()
+            .filter_map(|(id1, id2, is_real)| {
+                Some(MeshLine {
+                    start: position_of(id1)?,
+                    end: position_of(id2)?,
+                    color: if *is_real {
+                        EDGE_COLOR
+                    } else {
+                        VIRTUAL_EDGE_COLOR
+                    },
+                })
+            })
+            .collect();
+
+        Ok(MeshScene { spheres, lines })
+    }
+
+    /// Function to write the scene to a file
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is `FFIError::SaveFailed` if the file could not be written
+    pub fn save(&self, path: &Path, format: MeshFormat) -> Result<(), FFIError> {
+        let contents = match format {
+            MeshFormat::Gltf => self.to_gltf(),
+            MeshFormat::Obj => self.to_obj(),
+            MeshFormat::Ply => self.to_ply(),
+        };
+
+        fs::write(path, contents).map_err(|e| {
+            debug!("{:?}", e);
+            FFIError::SaveFailed
+        })
+    }
+
+    /// Function to write the scene as a glTF 2.0 file with its buffer embedded as a data uri
+    ///
+    /// The spheres are one triangle mesh and the lines are one line mesh, both with vertex colors
+    pub fn to_gltf(&self) -> String {
+        let triangles = self.triangles();
+        let line_positions: Vec<glam::Vec3> = self
+            .lines
+            .iter()
+            .flat_map(|line| [line.start, line.end])
+            .collect();
+        let line_colors: Vec<glam::Vec3> = self
+            .lines
+            .iter()
+            .flat_map(|line| [line.color, line.color])
+            .collect();
+
+        // Every view is a multiple of 4 bytes long, so no padding is needed between them
+        let mut buffer = Vec::new();
+        let mut views = Vec::new();
+        let mut add_view = |bytes: Vec<u8>, target: u32| {
+            views.push((buffer.len(), bytes.len(), target));
+            buffer.extend(bytes);
+            views.len() - 1
+        };
+        let vec3_bytes = |values: &[glam::Vec3]| -> Vec<u8> {
+            values
+                .iter()
+                .flat_map(|v| v.to_array())
+                .flat_map(f32::to_le_bytes)
+                .collect()
+        };
+
+        const ARRAY_BUFFER: u32 = 34962;
+        const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+        let mut accessors: Vec<String> = Vec::new();
+        let mut add_accessor = |accessor: String| {
+            accessors.push(accessor);
+            accessors.len() - 1
+        };
+
+        let mut meshes = Vec::new();
+        if !triangles.indices.is_empty() {
+            let positions = add_view(vec3_bytes(&triangles.positions), ARRAY_BUFFER);
+            let normals = add_view(vec3_bytes(&triangles.normals), ARRAY_BUFFER);
+            let colors = add_view(vec3_bytes(&triangles.colors), ARRAY_BUFFER);
+            let indices = add_view(
+                triangles
+                    .indices
+                    .iter()
+                    .flat_map(|i| i.to_le_bytes())
+                    .collect(),
+                ELEMENT_ARRAY_BUFFER,
+            );
+            let positions = add_accessor(vec3_accessor(positions, &triangles.positions, true));
+            let normals = add_accessor(vec3_accessor(normals, &triangles.normals, false));
+            let colors = add_accessor(vec3_accessor(colors, &triangles.colors, false));
+            let indices = add_accessor(format!(
+                "{{\"bufferView\":{},\"componentType\":5125,\"count\":{},\"type\":\"SCALAR\"}}",
+                indices,
+                triangles.indices.len()
+            ));
+            meshes.push(format!(
+                "{{\"name\":\"clusters\",\"primitives\":[{{\"attributes\":{{\"POSITION\":{},\"NORMAL\":{},\"COLOR_0\":{}}},\"indices\":{},\"mode\":4}}]}}",
+                positions, normals, colors, indices
+            ));
+        }
+        if !line_positions.is_empty() {
+            let positions = add_view(vec3_bytes(&line_positions), ARRAY_BUFFER);
+            let colors = add_view(vec3_bytes(&line_colors), ARRAY_BUFFER);
+            let positions = add_accessor(vec3_accessor(positions, &line_positions, true));
+            let colors = add_accessor(vec3_accessor(colors, &line_colors, false));
+            meshes.push(format!(
+                "{{\"name\":\"edges\",\"primitives\":[{{\"attributes\":{{\"POSITION\":{},\"COLOR_0\":{}}},\"mode\":1}}]}}",
+                positions, colors
+            ));
+        }
+
+        let nodes: Vec<String> = (0..meshes.len())
+            .map(|i| format!("{{\"mesh\":{}}}", i))
+            .collect();
+        let views: Vec<String> = views
+            .iter()
+            .map(|(offset, length, target)| {
+                format!(
+                    "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":{}}}",
+                    offset, length, target
+                )
+            })
+            .collect();
+
+        let mut out = String::from("{\"asset\":{\"version\":\"2.0\",\"generator\":\"clam_ffi\"}");
+        let _ = write!(
+            out,
+            ",\"scene\":0,\"scenes\":[{{\"nodes\":[{}]}}],\"nodes\":[{}],\"meshes\":[{}]",
+            (0..nodes.len())
+                .map(|i| i.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+            nodes.join(","),
+            meshes.join(",")
+        );
+        if !buffer.is_empty() {
+            let _ = write!(
+                out,
+                ",\"accessors\":[{}],\"bufferViews\":[{}],\"buffers\":[{{\"byteLength\":{},\"uri\":\"data:application/octet-stream;base64,{}\"}}]",
+                accessors.join(","),
+                views.join(","),
+                buffer.len(),
+                base64(&buffer)
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Function to write the scene as an OBJ file, with vertex colors after the positions
+    pub fn to_obj(&self) -> String {
+        let triangles = self.triangles();
+        let mut out = String::from("# clam_ffi mesh export\no clusters\n");
+        for (position, color) in triangles.positions.iter().zip(&triangles.colors) {
+            let _ = writeln!(
+                out,
+                "v {} {} {} {} {} {}",
+                position.x, position.y, position.z, color.x, color.y, color.z
+            );
+        }
+        for normal in &triangles.normals {
+            let _ = writeln!(out, "vn {} {} {}", normal.x, normal.y, normal.z);
+        }
+        for face in triangles.indices.chunks(3) {
+            let (a, b, c) = (face[0] + 1, face[1] + 1, face[2] + 1);
+            let _ = writeln!(out, "f {a}//{a} {b}//{b} {c}//{c}");
+        }
+
+        out.push_str("o edges\n");
+        let first = triangles.positions.len() + 1;
+        for line in &self.lines {
+            for point in [line.start, line.end] {
+                let _ = writeln!(
+                    out,
+                    "v {} {} {} {} {} {}",
+                    point.x, point.y, point.z, line.color.x, line.color.y, line.color.z
+                );
+            }
+        }
+        for i in 0..self.lines.len() {
+            let _ = writeln!(out, "l {} {}", first + 2 * i, first + 2 * i + 1);
+        }
+        out
+    }
+
+    /// Function to write the scene as an ASCII PLY file, with the lines as colored edges
+    pub fn to_ply(&self) -> String {
+        let triangles = self.triangles();
+        let num_vertices = triangles.positions.len() + 2 * self.lines.len();
+        let mut out = format!(
+            "ply\nformat ascii 1.0\ncomment clam_ffi mesh export\n\
+             element vertex {}\nproperty float x\nproperty float y\nproperty float z\n\
+             property float nx\nproperty float ny\nproperty float nz\n\
+             property uchar red\nproperty uchar green\nproperty uchar blue\n\
+             element face {}\nproperty list uchar uint vertex_indices\n\
+             element edge {}\nproperty int vertex1\nproperty int vertex2\n\
+             property uchar red\nproperty uchar green\nproperty uchar blue\nend_header\n",
+            num_vertices,
+            triangles.indices.len() / 3,
+            self.lines.len()
+        );
+
+        let line_vertices = self.lines.iter().flat_map(|line| {
+            [line.start, line.end].map(|point| (point, glam::Vec3::ZERO, line.color))
+        });
+        let vertices = triangles
+            .positions
+            .iter()
+            .zip(&triangles.normals)
+            .zip(&triangles.colors)
+            .map(|((&position, &normal), &color)| (position, normal, color))
+            .chain(line_vertices);
+        for (position, normal, color) in vertices {
+            let [r, g, b] = color_bytes(color);
+            let _ = writeln!(
+                out,
+                "{} {} {} {} {} {} {} {} {}",
+                position.x, position.y, position.z, normal.x, normal.y, normal.z, r, g, b
+            );
+        }
+        for face in triangles.indices.chunks(3) {
+            let _ = writeln!(out, "3 {} {} {}", face[0], face[1], face[2]);
+        }
+        let first = triangles.positions.len();
+        for (i, line) in self.lines.iter().enumerate() {
+            let [r, g, b] = color_bytes(line.color);
+            let _ = writeln!(
+                out,
+                "{} {} {} {} {}",
+                first + 2 * i,
+                first + 2 * i + 1,
+                r,
+                g,
+                b
+            );
+        }
+        out
+    }
+
+    /// Function to tessellate the spheres into triangles
+    fn triangles(&self) -> Triangles {
+        let mut triangles = Triangles {
+            positions: Vec::new(),
+            normals: Vec::new(),
+            colors: Vec::new(),
+            indices: Vec::new(),
+        };
+
+        for sphere in &self.spheres {
+            let first = triangles.positions.len() as u32;
+            for ring in 0..=SPHERE_RINGS {
+                let theta = std::f32::consts::PI * ring as f32 / SPHERE_RINGS as f32;
+                for segment in 0..=SPHERE_SEGMENTS {
+                    let phi = std::f32::consts::TAU * segment as f32 / SPHERE_SEGMENTS as f32;
+                    let normal = glam::Vec3::new(
+                        theta.sin() * phi.cos(),
+                        theta.cos(),
+                        theta.sin() * phi.sin(),
+                    );
+                    triangles
+                        .positions
+                        .push(sphere.center + normal * sphere.radius);
+                    triangles.normals.push(normal);
+                    triangles.colors.push(sphere.color);
+                }
+            }
+
+            let row = SPHERE_SEGMENTS + 1;
+            for ring in 0..SPHERE_RINGS {
+                for segment in 0..SPHERE_SEGMENTS {
+                    let a = first + ring * row + segment;
+                    let b = a + row;
+                    triangles.indices.extend([a, a + 1, b, a + 1, b + 1, b]);
+                }
+            }
+        }
+        triangles
+    }
+}
+
+/// Function to describe a glTF accessor of 3D float vectors, with the bounds that positions need
+fn vec3_accessor(view: usize, values: &[glam::Vec3], with_bounds: bool) -> String {
+    let bounds = if with_bounds {
+        let min = values.iter().fold(glam::Vec3::MAX, |a, &b| a.min(b));
+        let max = values.iter().fold(glam::Vec3::MIN, |a, &b| a.max(b));
+        format!(
+            ",\"min\":[{},{},{}],\"max\":[{},{},{}]",
+            min.x, min.y, min.z, max.x, max.y, max.z
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        "{{\"bufferView\":{},\"componentType\":5126,\"count\":{},\"type\":\"VEC3\"{}}}",
+        view,
+        values.len(),
+        bounds
+    )
+}
+
+/// Function to get the size of a cluster relative to the root, between 0 and 1
+fn relative_size(cluster: &Vertexf32, root: &Vertexf32, sizing: NodeSizing) -> f32 {
+    match sizing {
+        NodeSizing::Radius if root.radius() > 0.0 => cluster.radius() / root.radius(),
+        NodeSizing::Radius => 1.0,
+        NodeSizing::Cardinality => {
+            (cluster.cardinality() as f32 / root.cardinality() as f32).cbrt()
+        }
+    }
+}
+
+fn color_bytes(color: glam::Vec3) -> [u8; 3] {
+    (color.clamp(glam::Vec3::ZERO, glam::Vec3::ONE) * 255.0)
+        .round()
+        .to_array()
+        .map(|c| c as u8)
+}
+
+/// Function to encode bytes as standard base64 with padding
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_scene() -> MeshScene {
+        MeshScene {
+            spheres: vec![
+                MeshSphere {
+                    center: glam::Vec3::ZERO,
+                    radius: 1.0,
+                    color: glam::Vec3::new(1.0, 0.0, 0.0),
+                },
+                MeshSphere {
+                    center: glam::Vec3::new(5.0, 0.0, 0.0),
+                    radius: 0.5,
+                    color: glam::Vec3::new(0.0, 0.0, 1.0),
+                },
+            ],
+            lines: vec![MeshLine {
+                start: glam::Vec3::ZERO,
+                end: glam::Vec3::new(5.0, 0.0, 0.0),
+                color: EDGE_COLOR,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_mesh_export_formats() {
+        let scene = test_scene();
+        let vertices_per_sphere = ((SPHERE_RINGS + 1) * (SPHERE_SEGMENTS + 1)) as usize;
+        let faces_per_sphere = (2 * SPHERE_RINGS * SPHERE_SEGMENTS) as usize;
+
+        let obj = scene.to_obj();
+        assert_eq!(
+            obj.lines().filter(|l| l.starts_with("v ")).count(),
+            2 * vertices_per_sphere + 2
+        );
+        assert_eq!(
+            obj.lines().filter(|l| l.starts_with("f ")).count(),
+            2 * faces_per_sphere
+        );
+        let first = 2 * vertices_per_sphere + 1;
+        assert!(obj.ends_with(&format!("l {} {}\n", first, first + 1)));
+
+        let ply = scene.to_ply();
+        assert!(ply.contains(&format!("element vertex {}\n", 2 * vertices_per_sphere + 2)));
+        assert!(ply.contains(&format!("element face {}\n", 2 * faces_per_sphere)));
+        assert!(ply.ends_with(&format!(
+            "{} {} 153 153 153\n",
+            2 * vertices_per_sphere,
+            2 * vertices_per_sphere + 1
+        )));
+
+        let gltf = scene.to_gltf();
+        assert!(gltf.starts_with("{\"asset\":{\"version\":\"2.0\""));
+        assert!(gltf.contains("\"mode\":4"));
+        assert!(gltf.contains("\"mode\":1"));
+        assert_eq!(gltf.matches("\"min\":[").count(), 2);
+        assert!(gltf.contains("\"max\":[5,0,0]"));
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+}
@@ -1 +1,5 @@
+pub mod graph_export;
+pub mod graph_layout;
 pub mod load_save;
+pub mod mesh_export;
+pub mod tree_render;
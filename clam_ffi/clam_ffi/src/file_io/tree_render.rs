@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use abd_clam::Cluster;
+use resvg::{tiny_skia, usvg};
+
+use crate::debug;
+use crate::handle::handle::Handle;
+use crate::tree_layout::reingold_tilford;
+use crate::utils::coloring::ClusterColoring;
+use crate::utils::error::FFIError;
+use crate::utils::types::Vertexf32;
+
+/// The space around the tree, in pixels
+const MARGIN: f32 = 20.0;
+
+/// The file formats a tree can be rendered to
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl ImageFormat {
+    /// Function to get a format from the extension of a path, `.svg` or `.png`
+    pub fn from_path(path: &Path) -> Result<Self, FFIError> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("svg") => Ok(ImageFormat::Svg),
+            Some("png") => Ok(ImageFormat::Png),
+            _ => Err(FFIError::UnsupportedImageFormat),
+        }
+    }
+}
+
+/// The text drawn next to every cluster of a rendered tree
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum NodeLabel {
+    None,
+    /// The `offset-cardinality` name of the cluster
+    Name,
+    Cardinality,
+}
+
+impl NodeLabel {
+    /// Function to get a label from its name, `none`, `name` or `cardinality`
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "none" => Ok(NodeLabel::None),
+            "name" => Ok(NodeLabel::Name),
+            "cardinality" => Ok(NodeLabel::Cardinality),
+            _ => Err(format!("unknown node label {}", name)),
+        }
+    }
+}
+
+/// The options of a tree rendering
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct TreeRenderOptions {
+    pub coloring: ClusterColoring,
+    pub label: NodeLabel,
+    /// The deepest clusters drawn. Negative values draw the whole tree
+    pub max_depth: i32,
+    /// The distance between levels of the tree and the smallest distance between clusters on a
+    /// level, in pixels
+    pub spacing: f32,
+    /// The radius of the circle of every cluster, in pixels
+    pub node_radius: f32,
+    /// How much larger a png is than the svg it is rasterized from
+    pub png_scale: f32,
+}
+
+/// Function to render the Reingold-Tilford layout of the tree of a handle as an svg
+///
+/// # Arguments
+///
+/// * `handle` - The handle holding the tree
+/// * `options` - The coloring, labels, depth and sizes of the drawing
+///
+/// # Returns
+///
+/// A `Result` containing the svg or an `FFIError` if the clusters could not be colored
+pub fn render_svg(handle: &Handle, options: &TreeRenderOptions) -> Result<String, FFIError> {
+    let tree = handle.tree().ok_or(FFIError::HandleInitFailed)?;
+    let max_depth = if options.max_depth < 0 {
+        tree.depth() as i32
+    } else {
+        options.max_depth
+    };
+    let layout = reingold_tilford::positions(tree.root(), max_depth);
+
+    let clusters = layout
+        .positions
+        .iter()
+        .map(|(id, _)| handle.get_cluster_from_string(id.clone()))
+        .collect::<Result<Vec<&Vertexf32>, _>>()?;
+    let colors = handle.cluster_colors(&clusters, options.coloring)?;
+
+    // The layout has the root at the origin and the leaves below it, one unit apart
+    let min_x = layout
+        .positions
+        .iter()
+        .map(|(_, p)| p.x)
+        .fold(f32::INFINITY, f32::min);
+    let to_pixels = |p: glam::Vec3| {
+        (
+            MARGIN + (p.x - min_x) * options.spacing,
+            MARGIN + -p.y * options.spacing,
+        )
+    };
+    let points: Vec<(f32, f32)> = layout
+        .positions
+        .iter()
+        .map(|&(_, p)| to_pixels(p))
+        .collect();
+    let width = points.iter().map(|p| p.0).fold(0.0, f32::max) + 4.0 * MARGIN;
+    let height = points.iter().map(|p| p.1).fold(0.0, f32::max) + 2.0 * MARGIN;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n\
+         <g stroke=\"#808080\" stroke-width=\"1\">\n"
+    );
+    let indices: HashMap<&str, usize> = layout
+        .positions
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| (name.as_str(), i))
+        .collect();
+    let point_of = |id: &str| indices.get(id).map(|&i| points[i]);
+    for (parent, child) in &layout.edges {
+        if let (Some((x1, y1)), Some((x2, y2))) = (point_of(parent), point_of(child)) {
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\"/>"
+            );
+        }
+    }
+    svg.push_str("</g>\n<g stroke=\"#000000\" stroke-width=\"0.5\">\n");
+    for ((x, y), color) in points.iter().zip(&colors) {
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{x}\" cy=\"{y}\" r=\"{}\" fill=\"{}\"/>",
+            options.node_radius,
+            hex_color(*color)
+        );
+    }
+    svg.push_str("</g>\n");
+
+    if options.label != NodeLabel::None {
+        let font_size = (options.node_radius * 1.5).max(6.0);
+        let _ = writeln!(
+            svg,
+            "<g font-family=\"sans-serif\" font-size=\"{font_size}\" fill=\"#000000\">"
+        );
+        for (cluster, (x, y)) in clusters.iter().zip(&points) {
+            let text = match options.label {
+                NodeLabel::Name => cluster.name(),
+                _ => cluster.cardinality().to_string(),
+            };
+            let _ = writeln!(
+                svg,
+                "<text x=\"{}\" y=\"{}\">{}</text>",
+                x + options.node_radius + 2.0,
+                y + font_size / 3.0,
+                text
+            );
+        }
+        svg.push_str("</g>\n");
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// Function to rasterize an svg into a png
+///
+/// Text is drawn with the fonts installed on the system
+///
+/// # Arguments
+///
+/// * `svg` - The svg to rasterize
+/// * `scale` - How much larger the png is than the svg
+///
+/// # Returns
+///
+/// A `Result` containing the encoded png or `FFIError::RenderFailed` if it could not be drawn
+pub fn render_png(svg: &str, scale: f32) -> Result<Vec<u8>, FFIError> {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(svg, &options).map_err(|e| {
+        debug!("{:?}", e);
+        FFIError::RenderFailed
+    })?;
+
+    let scale = if scale > 0.0 { scale } else { 1.0 };
+    let size = tree
+        .size()
+        .to_int_size()
+        .scale_by(scale)
+        .ok_or(FFIError::RenderFailed)?;
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).ok_or(FFIError::RenderFailed)?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    pixmap.encode_png().map_err(|e| {
+        debug!("{:?}", e);
+        FFIError::RenderFailed
+    })
+}
+
+/// Function to render the tree of a handle to an svg or png file
+///
+/// # Returns
+///
+/// A `Result` that is an `FFIError` if the tree could not be rendered or the file written
+pub fn save_tree_render(
+    handle: &Handle,
+    path: &Path,
+    format: ImageFormat,
+    options: &TreeRenderOptions,
+) -> Result<(), FFIError> {
+    let svg = render_svg(handle, options)?;
+    let contents = match format {
+        ImageFormat::Svg => svg.into_bytes(),
+        ImageFormat::Png => render_png(&svg, options.png_scale)?,
+    };
+
+    fs::write(path, contents).map_err(|e| {
+        debug!("{:?}", e);
+        FFIError::SaveFailed
+    })
+}
+
+fn hex_color(color: glam::Vec3) -> String {
+    let [r, g, b] = (color.clamp(glam::Vec3::ZERO, glam::Vec3::ONE) * 255.0)
+        .round()
+        .to_array();
+    format!("#{:02x}{:02x}{:02x}", r as u8, g as u8, b as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::csv_readers::CsvOptions;
+    use crate::utils::data_paths::DataPaths;
+    use crate::utils::distances::{DistanceMetric, MetricParams};
+    use abd_clam::PartitionCriteria;
+
+    /// A handle over four points on a line, partitioned down to single points
+    fn test_handle() -> Handle {
+        let path = std::env::temp_dir().join("clam_ffi_test_render_svg.csv");
+        fs::write(&path, "0,0\n1,0\n2,0\n10,0\n").unwrap();
+        let data_paths = DataPaths {
+            features: path,
+            labels: None,
+        };
+        let csv_options = CsvOptions {
+            delimiter: None,
+            has_header: false,
+            label_column: None,
+        };
+
+        Handle::new(
+            "render_svg",
+            &data_paths,
+            &csv_options,
+            DistanceMetric::Euclidean,
+            &MetricParams::default(),
+            false,
+            &PartitionCriteria::new(true).with_min_cardinality(1),
+            Some(1),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let handle = test_handle();
+        let mut options = TreeRenderOptions {
+            coloring: ClusterColoring::Uniform,
+            label: NodeLabel::Name,
+            max_depth: -1,
+            spacing: 20.0,
+            node_radius: 5.0,
+            png_scale: 1.0,
+        };
+
+        // Four leaves under three parents, with a line from each parent to its children
+        let svg = render_svg(&handle, &options).unwrap();
+        assert_eq!(svg.matches("<circle ").count(), 7);
+        assert_eq!(svg.matches("<line ").count(), 6);
+        assert_eq!(svg.matches("<text ").count(), 7);
+        assert!(svg.contains(">0-4</text>"));
+
+        options.max_depth = 1;
+        options.label = NodeLabel::Cardinality;
+        let svg = render_svg(&handle, &options).unwrap();
+        assert_eq!(svg.matches("<circle ").count(), 3);
+        assert_eq!(svg.matches("<line ").count(), 2);
+        assert!(svg.contains(">4</text>"));
+        assert!(!svg.contains("0-4"));
+    }
+
+    #[test]
+    fn test_render_png() {
+        let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20\" height=\"10\">\
+                   <circle cx=\"5\" cy=\"5\" r=\"4\" fill=\"#ff0000\"/></svg>";
+
+        let png = render_png(svg, 2.0).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+        // The width and height are the first fields of the IHDR chunk
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 40);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 20);
+
+        assert_eq!(render_png("not an svg", 1.0), Err(FFIError::RenderFailed));
+        assert_eq!(hex_color(glam::Vec3::new(1.0, 0.5, 0.0)), "#ff8000");
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use super::physics_node::PhysicsNode;
+
+/// How far a cell must be, relative to its size, before its bodies are treated as one
+pub const THETA: f32 = 0.8;
+
+/// Cells smaller than this hold every body that falls in them instead of splitting further
+const MIN_CELL_SIZE: f32 = 1e-3;
+
+struct Cell {
+    center: glam::Vec3,
+    half_size: f32,
+    /// The sum of the positions of the bodies in the cell
+    position_sum: glam::Vec3,
+    count: u32,
+    /// The index of the first of the 8 children of the cell, if it was split
+    children: Option<usize>,
+    /// The bodies held by an unsplit cell, which is one unless they are too close to separate
+    bodies: Vec<usize>,
+}
+
+impl Cell {
+    fn new(center: glam::Vec3, half_size: f32) -> Self {
+        Cell {
+            center,
+            half_size,
+            position_sum: glam::Vec3::ZERO,
+            count: 0,
+            children: None,
+            bodies: Vec::new(),
+        }
+    }
+
+    fn octant(&self, position: glam::Vec3) -> usize {
+        (position.x >= self.center.x) as usize
+            | ((position.y >= self.center.y) as usize) << 1
+            | ((position.z >= self.center.z) as usize) << 2
+    }
+}
+
+/// An octree over the positions of the nodes of a graph, used to approximate the repulsion
+/// between every pair of nodes in O(n log n)
+pub struct Octree {
+    cells: Vec<Cell>,
+    positions: Vec<glam::Vec3>,
+}
+
+impl Octree {
+    /// Function to build an octree holding the given positions
+    pub fn new(positions: Vec<glam::Vec3>) -> Self {
+        let (min, max) = positions.iter().fold(
+            (glam::Vec3::splat(f32::MAX), glam::Vec3::splat(f32::MIN)),
+            |(min, max), p| (min.min(*p), max.max(*p)),
+        );
+        let half_size = ((max - min).max_element() / 2.0).max(MIN_CELL_SIZE);
+
+        let mut tree = Octree {
+            cells: vec![Cell::new((min + max) / 2.0, half_size)],
+            positions,
+        };
+        if tree.positions.is_empty() {
+            tree.cells[0].center = glam::Vec3::ZERO;
+        }
+        for body in 0..tree.positions.len() {
+            tree.insert(body);
+        }
+        tree
+    }
+
+    fn insert(&mut self, body: usize) {
+        let position = self.positions[body];
+        let mut cell = 0;
+
+        loop {
+            self.cells[cell].position_sum += position;
+            self.cells[cell].count += 1;
+
+            if let Some(first) = self.cells[cell].children {
+                cell = first + self.cells[cell].octant(position);
+            } else if self.cells[cell].count == 1 || self.cells[cell].half_size < MIN_CELL_SIZE {
+                self.cells[cell].bodies.push(body);
+                return;
+            } else {
+                self.split(cell);
+                let first = self.cells[cell].children.unwrap();
+                let previous = self.cells[cell].bodies.pop().unwrap();
+                let previous_position = self.positions[previous];
+                let child = first + self.cells[cell].octant(previous_position);
+                self.cells[child].position_sum += previous_position;
+                self.cells[child].count += 1;
+                self.cells[child].bodies.push(previous);
+                cell = first + self.cells[cell].octant(position);
+            }
+        }
+    }
+
+    fn split(&mut self, cell: usize) {
+        let (center, half_size) = (self.cells[cell].center, self.cells[cell].half_size / 2.0);
+        let first = self.cells.len();
+        for octant in 0..8 {
+            let offset = glam::Vec3::new(
+                if octant & 1 == 0 {
+                    -half_size
+                } else {
+                    half_size
+                },
+                if octant & 2 == 0 {
+                    -half_size
+                } else {
+                    half_size
+                },
+                if octant & 4 == 0 {
+                    -half_size
+                } else {
+                    half_size
+                },
+            );
+            self.cells.push(Cell::new(center + offset, half_size));
+        }
+        self.cells[cell].children = Some(first);
+    }
+
+    /// Function to approximate the repulsion pushing a body away from every other body
+    ///
+    /// Every body pushes with a force of `strength / distance`. Cells that are far enough away,
+    /// by `theta`, push as a single body at their center of mass.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The index of the body, in the positions the tree was built from
+    /// * `theta` - The largest ratio of the size of a cell to its distance that is approximated
+    /// * `strength` - The force between two bodies at a distance of one
+    pub fn repulsion(&self, body: usize, theta: f32, strength: f32) -> glam::Vec3 {
+        let position = self.positions[body];
+        let mut force = glam::Vec3::ZERO;
+        let mut stack = vec![0];
+
+        while let Some(index) = stack.pop() {
+            let cell = &self.cells[index];
+            let (mut position_sum, mut count) = (cell.position_sum, cell.count);
+            if cell.bodies.contains(&body) {
+                position_sum -= position;
+                count -= 1;
+            }
+            if count == 0 {
+                continue;
+            }
+
+            let away = position - position_sum / count as f32;
+            let distance = away.length();
+            match cell.children {
+                Some(first) if 2.0 * cell.half_size >= theta * distance => {
+                    stack.extend(first..first + 8);
+                }
+                _ => {
+                    // Bodies sharing a position with this one have no direction to push in
+                    if distance > f32::EPSILON {
+                        force += away * (strength * count as f32 / (distance * distance));
+                    }
+                }
+            }
+        }
+
+        force
+    }
+}
+
+/// Function to push every node of a graph away from the others with Barnes-Hut repulsion
+///
+/// # Arguments
+///
+/// * `graph` - The nodes to move
+/// * `scalar` - The scale of the layout, which sets the distance the repulsion acts over
+/// * `spring_constant` - The stiffness of the springs, which the repulsion is balanced against
+pub fn accumulate_repulsion_forces(
+    graph: &mut HashMap<String, PhysicsNode>,
+    scalar: f32,
+    spring_constant: f32,
+) {
+    // Build the tree in the same order every time so the approximation does not depend on the map
+    let mut ids: Vec<&String> = graph.keys().collect();
+    ids.sort();
+    let tree = Octree::new(ids.iter().map(|id| graph[*id].get_position()).collect());
+
+    let strength = spring_constant * scalar * scalar / ids.len().max(1) as f32;
+    let forces: Vec<(String, glam::Vec3)> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| ((*id).clone(), tree.repulsion(i, THETA, strength)))
+        .collect();
+
+    for (id, force) in forces {
+        if let Some(node) = graph.get_mut(&id) {
+            node.accelerate(force);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_octree_repulsion() {
+        let positions: Vec<glam::Vec3> = (0..50)
+            .map(|i| {
+                let i = i as f32;
+                glam::Vec3::new((i * 7.3) % 11.0, (i * 3.1) % 5.0, (i * 1.7) % 13.0)
+            })
+            .collect();
+        let tree = Octree::new(positions.clone());
+
+        for body in 0..positions.len() {
+            let exact: glam::Vec3 = positions
+                .iter()
+                .filter(|p| p.distance(positions[body]) > f32::EPSILON)
+                .map(|p| (positions[body] - *p) / positions[body].distance_squared(*p))
+                .sum();
+
+            // A theta of zero never approximates, so it matches the sum over every pair
+            assert!(tree.repulsion(body, 0.0, 1.0).distance(exact) < 1e-3 * exact.length());
+            assert!(tree.repulsion(body, THETA, 1.0).distance(exact) < 0.2 * exact.length());
+        }
+
+        let tree = Octree::new(vec![glam::Vec3::ONE, glam::Vec3::ONE]);
+        assert_eq!(tree.repulsion(0, THETA, 1.0), glam::Vec3::ZERO);
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+
+use abd_clam::Cluster;
+use lru::LruCache;
+
+use crate::utils::clam_tree::ClamTree;
+use crate::utils::types::Vertexf32;
+
+/// Graphs with at most this many clusters cache the distance between every pair of them
+pub const MATRIX_LIMIT: usize = 2048;
+
+/// The number of distances kept for graphs too large for a matrix
+pub const LRU_CAPACITY: usize = 1 << 20;
+
+/// How a distance cache stores its distances
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum DistanceCacheKind {
+    /// Every pair of clusters has a slot, so nothing is ever evicted
+    Matrix,
+    /// The most recently used pairs are kept, up to a fixed capacity
+    Lru,
+}
+
+/// How well a distance cache has been doing since it was created
+#[repr(C)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct DistanceCacheStats {
+    pub kind: DistanceCacheKind,
+    /// The number of distances found in the cache
+    pub hits: u64,
+    /// The number of distances that were computed with the metric
+    pub metric_calls: u64,
+    /// The number of distances currently in the cache
+    pub entries: u64,
+    /// The most distances the cache can hold
+    pub capacity: u64,
+    /// The fraction of lookups that were hits, or zero before the first lookup
+    pub hit_rate: f32,
+}
+
+enum Storage {
+    /// The lower triangle of the matrix, row by row, with NaN for distances not yet computed
+    Matrix(Vec<f32>),
+    Lru(LruCache<(usize, usize), f32>),
+}
+
+/// A cache of the distances between the centers of the clusters of a graph, so that the physics
+/// does not compute the same distance with the metric every frame
+pub struct DistanceCache {
+    /// The index of every cluster of the graph, by offset and cardinality
+    indices: HashMap<(usize, usize), usize>,
+    storage: Storage,
+    hits: u64,
+    metric_calls: u64,
+    entries: usize,
+}
+
+impl DistanceCache {
+    /// Function to create a cache for the clusters of a graph, with a matrix if the graph has at
+    /// most `MATRIX_LIMIT` clusters and an LRU cache of `LRU_CAPACITY` distances otherwise
+    pub fn new(clusters: &[&Vertexf32]) -> Self {
+        if clusters.len() <= MATRIX_LIMIT {
+            Self::with_kind(clusters, DistanceCacheKind::Matrix, 0)
+        } else {
+            Self::with_kind(clusters, DistanceCacheKind::Lru, LRU_CAPACITY)
+        }
+    }
+
+    /// Function to create a cache with the given storage
+    ///
+    /// # Arguments
+    ///
+    /// * `clusters` - The clusters whose distances are cached
+    /// * `kind` - Whether to keep every pair or only the most recently used ones
+    /// * `capacity` - The number of distances an LRU cache holds. Ignored by a matrix
+    pub fn with_kind(clusters: &[&Vertexf32], kind: DistanceCacheKind, capacity: usize) -> Self {
+        let indices = clusters
+            .iter()
+            .enumerate()
+            .map(|(i, c)| ((c.offset(), c.cardinality()), i))
+            .collect();
+        let storage = match kind {
+            DistanceCacheKind::Matrix => {
+                Storage::Matrix(vec![f32::NAN; triangle_index(0, clusters.len())])
+            }
+            DistanceCacheKind::Lru => Storage::Lru(LruCache::new(
+                NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN),
+            )),
+        };
+
+        DistanceCache {
+            indices,
+            storage,
+            hits: 0,
+            metric_calls: 0,
+            entries: 0,
+        }
+    }
+
+    /// Function to get the distance between the centers of two clusters, computing it with the
+    /// metric of the tree only if it is not cached
+    pub fn distance(&mut self, tree: &ClamTree, left: &Vertexf32, right: &Vertexf32) -> f32 {
+        match (self.index(left), self.index(right)) {
+            (Some(i), Some(j)) => self.lookup(i, j, || tree.cluster_distance(left, right)),
+            // Clusters outside the graph are not cached
+            _ => {
+                self.metric_calls += 1;
+                tree.cluster_distance(left, right)
+            }
+        }
+    }
+
+    /// Function to get the cached distance between the clusters at two indices, or cache the
+    /// distance given by `metric`
+    fn lookup(&mut self, i: usize, j: usize, metric: impl FnOnce() -> f32) -> f32 {
+        let (i, j) = (i.min(j), i.max(j));
+        let cached = match &mut self.storage {
+            Storage::Matrix(values) => Some(values[triangle_index(i, j)]).filter(|d| !d.is_nan()),
+            Storage::Lru(cache) => cache.get(&(i, j)).copied(),
+        };
+        if let Some(distance) = cached {
+            self.hits += 1;
+            return distance;
+        }
+
+        let distance = metric();
+        self.metric_calls += 1;
+        match &mut self.storage {
+            Storage::Matrix(values) => {
+                values[triangle_index(i, j)] = distance;
+                self.entries += 1;
+            }
+            Storage::Lru(cache) => {
+                cache.put((i, j), distance);
+                self.entries = cache.len();
+            }
+        }
+        distance
+    }
+
+    /// Function to get the number of hits and metric calls of the cache and how full it is
+    pub fn stats(&self) -> DistanceCacheStats {
+        let (kind, capacity) = match &self.storage {
+            Storage::Matrix(values) => (DistanceCacheKind::Matrix, values.len()),
+            Storage::Lru(cache) => (DistanceCacheKind::Lru, cache.cap().get()),
+        };
+        let lookups = self.hits + self.metric_calls;
+
+        DistanceCacheStats {
+            kind,
+            hits: self.hits,
+            metric_calls: self.metric_calls,
+            entries: self.entries as u64,
+            capacity: capacity as u64,
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                self.hits as f32 / lookups as f32
+            },
+        }
+    }
+
+    fn index(&self, cluster: &Vertexf32) -> Option<usize> {
+        self.indices
+            .get(&(cluster.offset(), cluster.cardinality()))
+            .copied()
+    }
+}
+
+/// The position of the pair `(i, j)`, with `i <= j`, in the lower triangle of a matrix. The
+/// triangle of an `n` by `n` matrix has `triangle_index(0, n)` slots
+fn triangle_index(i: usize, j: usize) -> usize {
+    j * (j + 1) / 2 + i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_cache() {
+        let mut cache = DistanceCache::with_kind(&[], DistanceCacheKind::Matrix, 0);
+        cache.storage = Storage::Matrix(vec![f32::NAN; triangle_index(0, 4)]);
+
+        assert_eq!(cache.lookup(1, 3, || 2.0), 2.0);
+        // The pair is cached in either order, so the metric is not called again
+        assert_eq!(cache.lookup(3, 1, || unreachable!()), 2.0);
+        assert_eq!(cache.lookup(3, 3, || 0.0), 0.0);
+        assert_eq!(
+            cache.stats(),
+            DistanceCacheStats {
+                kind: DistanceCacheKind::Matrix,
+                hits: 1,
+                metric_calls: 2,
+                entries: 2,
+                capacity: 10,
+                hit_rate: 1.0 / 3.0,
+            }
+        );
+
+        let mut cache = DistanceCache::with_kind(&[], DistanceCacheKind::Lru, 2);
+        cache.lookup(0, 1, || 1.0);
+        cache.lookup(0, 2, || 2.0);
+        cache.lookup(0, 1, || unreachable!());
+        // The least recently used pair is evicted to make room
+        cache.lookup(1, 2, || 3.0);
+        assert_eq!(cache.lookup(0, 2, || 4.0), 4.0);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.metric_calls), (1, 4));
+        assert_eq!((stats.entries, stats.capacity), (2, 2));
+    }
+}
@@ -0,0 +1,115 @@
+/// The number of frames in a row whose energy must barely change before a layout has converged
+pub const SETTLED_FRAMES: usize = 10;
+
+/// The energy of a force-directed graph after a frame
+#[repr(C)]
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct FrameEnergy {
+    /// The sum of half the mass times the squared speed of every node
+    pub kinetic: f32,
+    /// The energy stored in the springs of the graph by stretching or compressing them
+    pub spring: f32,
+}
+
+impl FrameEnergy {
+    pub fn total(&self) -> f32 {
+        self.kinetic + self.spring
+    }
+}
+
+/// The energy of every frame of a simulation and whether it has converged
+#[derive(Debug, Clone, Default)]
+pub struct EnergyHistory {
+    frames: Vec<FrameEnergy>,
+    settled_frames: usize,
+    converged_at: Option<usize>,
+}
+
+impl EnergyHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Function to add the energy of the next frame
+    ///
+    /// The simulation converges once the total energy changes by at most `threshold`, relative to
+    /// the frame before, for `SETTLED_FRAMES` frames in a row. It stays converged after that.
+    ///
+    /// # Arguments
+    ///
+    /// * `energy` - The energy after the frame
+    /// * `threshold` - The largest relative change of a settled frame. Zero or less never converges
+    ///
+    /// # Returns
+    ///
+    /// Whether the simulation has converged
+    pub fn record(&mut self, energy: FrameEnergy, threshold: f32) -> bool {
+        if let Some(previous) = self.frames.last() {
+            let change = (energy.total() - previous.total()).abs();
+            if threshold > 0.0 && change <= threshold * previous.total().max(f32::EPSILON) {
+                self.settled_frames += 1;
+            } else {
+                self.settled_frames = 0;
+            }
+        }
+        self.frames.push(energy);
+
+        if self.converged_at.is_none() && self.settled_frames >= SETTLED_FRAMES {
+            self.converged_at = Some(self.frames.len());
+        }
+        self.converged_at.is_some()
+    }
+
+    /// Function to forget that the simulation converged, keeping the energy of its frames
+    pub fn restart(&mut self) {
+        self.settled_frames = 0;
+        self.converged_at = None;
+    }
+
+    /// Function to get the energy of every frame so far, in order
+    pub fn frames(&self) -> &[FrameEnergy] {
+        &self.frames
+    }
+
+    /// Function to get the number of frames it took to converge, or `None` if it has not
+    pub fn converged_at(&self) -> Option<usize> {
+        self.converged_at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_energy_history() {
+        let frame = |total: f32| FrameEnergy {
+            kinetic: total / 2.0,
+            spring: total / 2.0,
+        };
+
+        let mut history = EnergyHistory::new();
+        for i in 0..20 {
+            assert!(!history.record(frame(100.0 / (i + 1) as f32), 1e-3));
+        }
+        // The first frame at the new energy is a large change, so it does not count as settled
+        for i in 0..=SETTLED_FRAMES {
+            assert_eq!(history.record(frame(1.0), 1e-3), i == SETTLED_FRAMES);
+        }
+        assert_eq!(history.converged_at(), Some(21 + SETTLED_FRAMES));
+
+        // A later jump does not undo the convergence
+        assert!(history.record(frame(50.0), 1e-3));
+        assert_eq!(history.frames().len(), 22 + SETTLED_FRAMES);
+
+        // A restarted history keeps its frames but has to settle again
+        history.restart();
+        assert!(!history.record(frame(50.0), 1e-3));
+        assert_eq!(history.frames().len(), 23 + SETTLED_FRAMES);
+
+        let mut history = EnergyHistory::new();
+        for _ in 0..2 * SETTLED_FRAMES {
+            assert!(!history.record(frame(1.0), 0.0));
+        }
+    }
+}
@@ -1,6 +1,14 @@
+use std::ffi::c_char;
+
 use crate::{
     debug,
-    ffi_impl::cluster_data::ClusterData,
+    ffi_impl::{
+        cluster_data::ClusterData, cluster_data_wrapper::ClusterDataWrapper,
+        lib_impl::c_char_to_str,
+    },
+    graph::{
+        distance_cache::DistanceCacheStats, energy::FrameEnergy, physics_settings::PhysicsSettings,
+    },
     utils::{error::FFIError, types::InHandlePtr},
     CBFnNodeVisitor, CBFnNodeVisitorMut,
 };
@@ -18,13 +26,120 @@ pub unsafe fn physics_update_async_impl(
     }
 }
 
+/// Function to update the physics asynchronously and copy the new positions to an array
+///
+/// The nodes are in the order given by `visit_physics_node_order_impl`
+///
+/// # Safety
+///
+/// This function is unsafe because it writes to the array passed to it
+///
+/// # Arguments
+///
+/// * `context` - A pointer to the handle
+/// * `out_positions` - A pointer to an array of `len` floats, three for every node
+/// * `len` - The length of the array, at least three times the number of nodes
+///
+/// # Returns
+///
+/// An `FFIError` enum, which is `BufferTooSmall` if the array has no room for every node
+pub unsafe fn physics_update_positions_impl(
+    context: InHandlePtr,
+    out_positions: *mut f32,
+    len: i32,
+) -> FFIError {
+    let handle = match context {
+        Some(handle) => handle,
+        None => return FFIError::NullPointerPassed,
+    };
+    if out_positions.is_null() || len < 0 {
+        return FFIError::NullPointerPassed;
+    }
+
+    handle.physics_update_positions(std::slice::from_raw_parts_mut(out_positions, len as usize))
+}
+
+/// Function to copy the position of every node of the physics to an array, without waiting for
+/// the physics thread
+///
+/// The nodes are in the order given by `visit_physics_node_order_impl`
+///
+/// # Safety
+///
+/// This function is unsafe because it writes to the array passed to it
+///
+/// # Arguments
+///
+/// * `context` - A pointer to the handle
+/// * `out_positions` - A pointer to an array of `len` floats, three for every node
+/// * `len` - The length of the array, at least three times the number of nodes
+///
+/// # Returns
+///
+/// An `FFIError` enum, which is `BufferTooSmall` if the array has no room for every node
+pub unsafe fn copy_physics_positions_impl(
+    context: InHandlePtr,
+    out_positions: *mut f32,
+    len: i32,
+) -> FFIError {
+    let handle = match context {
+        Some(handle) => handle,
+        None => return FFIError::NullPointerPassed,
+    };
+    if out_positions.is_null() || len < 0 {
+        return FFIError::NullPointerPassed;
+    }
+
+    handle.copy_physics_positions(std::slice::from_raw_parts_mut(out_positions, len as usize))
+}
+
+pub fn get_physics_node_count_impl(context: InHandlePtr) -> i32 {
+    if let Some(handle) = context {
+        if let Some(order) = handle.physics_node_order() {
+            return order.len() as i32;
+        }
+    }
+    -1
+}
+
+/// Function to pass the name of every node of the physics to a visitor, in the order their
+/// positions are copied to arrays
+///
+/// # Arguments
+///
+/// * `context` - A pointer to the handle
+/// * `visitor` - The node visitor function, called once for every node
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub fn visit_physics_node_order_impl(context: InHandlePtr, visitor: CBFnNodeVisitor) -> FFIError {
+    let handle = match context {
+        Some(handle) => handle,
+        None => return FFIError::NullPointerPassed,
+    };
+
+    match handle.physics_node_order() {
+        Some(order) => {
+            for id in order {
+                let baton = ClusterDataWrapper::from_physics(id, glam::Vec3::ZERO);
+                visitor(Some(baton.data()));
+            }
+            FFIError::Ok
+        }
+        None => FFIError::PhysicsAlreadyShutdown,
+    }
+}
+
 pub fn init_force_directed_graph_impl(
     context: InHandlePtr,
     scalar: f32,
     max_iters: i32,
+    settings: Option<&PhysicsSettings>,
 ) -> FFIError {
     if let Some(handle) = context {
-        match graph_builder::build_force_directed_graph_async(handle, scalar, max_iters) {
+        let settings = settings.copied().unwrap_or_default();
+        match graph_builder::build_force_directed_graph_async(handle, scalar, settings, max_iters) {
             Ok(g) => {
                 handle.set_graph(g);
                 FFIError::Ok
@@ -65,10 +180,215 @@ pub fn get_num_edges_in_graph_impl(ptr: InHandlePtr) -> i32 {
     -1
 }
 
+pub fn set_physics_settings_impl(
+    context: InHandlePtr,
+    settings: Option<&PhysicsSettings>,
+) -> FFIError {
+    if let (Some(handle), Some(settings)) = (context, settings) {
+        handle.set_physics_settings(*settings)
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+pub fn pause_physics_impl(context: InHandlePtr) -> FFIError {
+    if let Some(handle) = context {
+        handle.pause_physics()
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+pub fn resume_physics_impl(context: InHandlePtr) -> FFIError {
+    if let Some(handle) = context {
+        handle.resume_physics()
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+pub fn step_physics_impl(context: InHandlePtr, frames: i32) -> FFIError {
+    if let Some(handle) = context {
+        handle.step_physics(frames.max(0) as u32)
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+pub fn restart_physics_impl(context: InHandlePtr, max_iters: i32) -> FFIError {
+    if let Some(handle) = context {
+        handle.restart_physics(max_iters)
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+/// Function to hold the node of a cluster at a position while the rest of the physics runs
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the pointers passed to it
+///
+/// # Arguments
+///
+/// * `context` - A pointer to the handle
+/// * `cluster_id` - A pointer to the name of the cluster
+/// * `position` - Where to hold the node
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub unsafe fn pin_node_impl(
+    context: InHandlePtr,
+    cluster_id: *const c_char,
+    position: glam::Vec3,
+) -> FFIError {
+    match (context, c_char_to_str(cluster_id)) {
+        (Some(handle), Ok(id)) => handle.pin_node(id, position),
+        (None, _) => FFIError::NullPointerPassed,
+        (_, Err(e)) => e,
+    }
+}
+
+/// Function to drag a pinned node to another position
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the pointers passed to it
+///
+/// # Arguments
+///
+/// * `context` - A pointer to the handle
+/// * `cluster_id` - A pointer to the name of the cluster
+/// * `position` - Where to hold the node now
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub unsafe fn move_pinned_node_impl(
+    context: InHandlePtr,
+    cluster_id: *const c_char,
+    position: glam::Vec3,
+) -> FFIError {
+    match (context, c_char_to_str(cluster_id)) {
+        (Some(handle), Ok(id)) => handle.move_pinned_node(id, position),
+        (None, _) => FFIError::NullPointerPassed,
+        (_, Err(e)) => e,
+    }
+}
+
+/// Function to let the physics move a pinned node again
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences the pointers passed to it
+///
+/// # Arguments
+///
+/// * `context` - A pointer to the handle
+/// * `cluster_id` - A pointer to the name of the cluster
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub unsafe fn unpin_node_impl(context: InHandlePtr, cluster_id: *const c_char) -> FFIError {
+    match (context, c_char_to_str(cluster_id)) {
+        (Some(handle), Ok(id)) => handle.unpin_node(id),
+        (None, _) => FFIError::NullPointerPassed,
+        (_, Err(e)) => e,
+    }
+}
+
+pub fn get_physics_settings_impl(
+    context: InHandlePtr,
+    out_settings: Option<&mut PhysicsSettings>,
+) -> FFIError {
+    if let (Some(handle), Some(out_settings)) = (context, out_settings) {
+        *out_settings = handle.physics_settings();
+        FFIError::Ok
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
+pub fn get_energy_history_len_impl(ptr: InHandlePtr) -> i32 {
+    if let Some(handle) = ptr {
+        if let Some(history) = handle.energy_history() {
+            return history.frames().len() as i32;
+        }
+    }
+    -1
+}
+
+/// Function to copy the energy of every frame of the physics into an array
+///
+/// # Safety
+///
+/// This function is unsafe because it writes to the array passed to it
+///
+/// # Arguments
+///
+/// * `ptr` - A pointer to the handle
+/// * `out_energy` - A pointer to an array of `len` frames
+/// * `len` - The length of the array. Frames past it are not copied
+///
+/// # Returns
+///
+/// An `FFIError` enum
+pub unsafe fn get_energy_history_impl(
+    ptr: InHandlePtr,
+    out_energy: *mut FrameEnergy,
+    len: i32,
+) -> FFIError {
+    let handle = match ptr {
+        Some(handle) => handle,
+        None => return FFIError::NullPointerPassed,
+    };
+    if out_energy.is_null() || len < 0 {
+        return FFIError::NullPointerPassed;
+    }
+
+    match handle.energy_history() {
+        Some(history) => {
+            let frames = history.frames();
+            let count = frames.len().min(len as usize);
+            std::slice::from_raw_parts_mut(out_energy, count).copy_from_slice(&frames[..count]);
+            FFIError::Ok
+        }
+        None => FFIError::NoEnergyHistory,
+    }
+}
+
+pub fn get_converged_iteration_impl(ptr: InHandlePtr) -> i32 {
+    if let Some(handle) = ptr {
+        if let Some(frames) = handle.energy_history().and_then(|h| h.converged_at()) {
+            return frames as i32;
+        }
+    }
+    -1
+}
+
+pub fn get_distance_cache_stats_impl(
+    ptr: InHandlePtr,
+    out_stats: Option<&mut DistanceCacheStats>,
+) -> FFIError {
+    if let (Some(handle), Some(out_stats)) = (ptr, out_stats) {
+        match handle.distance_cache_stats() {
+            Some(stats) => {
+                *out_stats = stats;
+                FFIError::Ok
+            }
+            None => FFIError::NoDistanceCacheStats,
+        }
+    } else {
+        FFIError::NullPointerPassed
+    }
+}
+
 pub fn get_num_graph_components_impl(ptr: InHandlePtr) -> i32 {
     if let Some(handle) = ptr {
-        if let Some(clam_graph) = handle.clam_graph() {
-            return clam_graph.find_component_clusters().len() as i32;
+        if let Some(graph) = handle.graph_selection() {
+            return graph.num_components() as i32;
         }
     }
     -1
@@ -1,12 +1,19 @@
 use abd_clam::Cluster;
+use rand::rngs::StdRng;
 use rand::seq::IteratorRandom;
 
+use super::barnes_hut;
+use super::distance_cache::{DistanceCache, DistanceCacheStats};
+use super::energy::{EnergyHistory, FrameEnergy};
 use super::physics_node::PhysicsNode;
+use super::physics_settings::PhysicsSettings;
 use super::spring::Spring;
 use crate::ffi_impl::cluster_data_wrapper::ClusterDataWrapper;
+use crate::utils::clam_tree::ClamTree;
 use crate::utils::error::FFIError;
-use crate::utils::types::{Graphf32, Treef32};
-use crate::{debug, utils, CBFnNodeVisitor, CBFnNodeVisitorMut};
+use crate::utils::helpers;
+use crate::utils::types::{Graphf32, Vertexf32};
+use crate::{debug, CBFnNodeVisitor, CBFnNodeVisitorMut};
 use std::collections::HashMap;
 
 use std::sync::{Condvar, Mutex};
@@ -14,6 +21,9 @@ use std::sync::{Condvar, Mutex};
 pub struct Status {
     pub data_ready: bool,
     pub force_shutdown: bool,
+    /// A paused graph only moves by the frames queued in `steps`
+    pub paused: bool,
+    pub steps: u32,
 }
 
 impl Status {
@@ -22,17 +32,48 @@ impl Status {
             // this prevents thread from beginning work immediately - true
             data_ready: true,
             force_shutdown: false,
+            paused: false,
+            steps: 0,
+        }
+    }
+}
+
+/// The forces that spread out clusters that are not joined by an edge
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ForceModel {
+    /// Every frame, each cluster is pulled towards its distance from 3 random clusters
+    RandomSprings,
+    /// Every cluster pushes the others away, approximated with an octree in O(n log n) and
+    /// without computing any distances in the tree
+    BarnesHut,
+}
+
+impl ForceModel {
+    /// Function to get a force model from its name, `random_springs` or `barnes_hut`
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "random_springs" => Ok(ForceModel::RandomSprings),
+            "barnes_hut" => Ok(ForceModel::BarnesHut),
+            _ => Err(format!("unknown force model {}", name)),
         }
     }
 }
 
 pub struct ForceDirectedGraph {
     graph: Mutex<(Status, HashMap<String, PhysicsNode>)>,
+    /// The names of the nodes, sorted once when the graph is created, in the order their positions
+    /// are copied to the host
+    order: Vec<String>,
     edges: Vec<Spring>,
     pub max_edge_len: f32,
     pub scalar: f32,
+    settings: Mutex<PhysicsSettings>,
     cond_var: Condvar,
     max_iters: i32,
+    rng: Mutex<StdRng>,
+    distance_cache: Mutex<DistanceCache>,
+    energy: Mutex<EnergyHistory>,
 }
 
 impl ForceDirectedGraph {
@@ -40,41 +81,143 @@ impl ForceDirectedGraph {
         graph: HashMap<String, PhysicsNode>,
         edges: Vec<Spring>,
         scalar: f32,
+        settings: PhysicsSettings,
+        distance_cache: DistanceCache,
         max_iters: i32,
+        seed: Option<u64>,
     ) -> Self {
         let max_edge_len = Self::calc_max_edge_len(&edges);
+        let mut order: Vec<String> = graph.keys().cloned().collect();
+        order.sort();
 
         ForceDirectedGraph {
             graph: Mutex::new((Status::new(), graph)),
+            order,
             edges,
             max_edge_len,
             scalar,
+            settings: Mutex::new(settings),
             cond_var: Condvar::new(),
             max_iters,
+            rng: Mutex::new(helpers::seeded_rng(seed)),
+            distance_cache: Mutex::new(distance_cache),
+            energy: Mutex::new(EnergyHistory::new()),
         }
     }
 
-    pub fn update(&mut self, clam_graph: &Graphf32, tree: &Treef32) {
-        match self.graph.get_mut() {
-            Ok(g) => {
+    pub fn update(&mut self, clam_graph: &Graphf32, tree: &ClamTree) {
+        let settings = self.settings();
+        match self.graph.lock() {
+            Ok(mut g) => {
                 for spring in self.edges.iter() {
-                    spring.move_nodes(&mut g.1, self.max_edge_len, self.scalar);
+                    spring.move_nodes(
+                        &mut g.1,
+                        self.max_edge_len,
+                        self.scalar,
+                        settings.spring_constant,
+                    );
                 }
 
-                Self::accumulate_random_forces(
+                self.accumulate_global_forces(
                     &mut g.1,
-                    clam_graph,
+                    clam_graph.ordered_clusters(),
                     tree,
-                    self.max_edge_len,
-                    self.scalar,
+                    &settings,
                 );
 
-                Self::apply_forces(&mut g.1);
+                Self::apply_forces(&mut g.1, &settings);
+                self.record_energy(&g.1, &settings);
             }
-            Err(e) => {}
+            _ => {}
+        }
+    }
+
+    /// Function to get the number of frames it took the energy of the graph to settle
+    ///
+    /// # Returns
+    ///
+    /// The number of frames, or `None` if the graph has not converged
+    pub fn converged_at(&self) -> Option<usize> {
+        match self.energy.lock() {
+            Ok(energy) => energy.converged_at(),
+            Err(e) => e.into_inner().converged_at(),
         }
     }
 
+    /// Function to copy the energy of every frame so far
+    pub fn energy_history(&self) -> EnergyHistory {
+        match self.energy.lock() {
+            Ok(energy) => energy.clone(),
+            Err(e) => e.into_inner().clone(),
+        }
+    }
+
+    /// Function to add the energy of the nodes and springs after a frame to the history
+    fn record_energy(&self, graph: &HashMap<String, PhysicsNode>, settings: &PhysicsSettings) {
+        let energy = FrameEnergy {
+            kinetic: graph.values().map(|node| node.kinetic_energy()).sum(),
+            spring: self
+                .edges
+                .iter()
+                .map(|spring| {
+                    spring.energy(
+                        graph,
+                        self.max_edge_len,
+                        self.scalar,
+                        settings.spring_constant,
+                    )
+                })
+                .sum(),
+        };
+
+        if let Ok(mut history) = self.energy.lock() {
+            history.record(energy, settings.convergence_threshold);
+        }
+    }
+
+    /// Function to copy the settings the physics is running with
+    pub fn settings(&self) -> PhysicsSettings {
+        match self.settings.lock() {
+            Ok(settings) => *settings,
+            Err(e) => *e.into_inner(),
+        }
+    }
+
+    /// Function to change the settings of the physics, which the next frame runs with
+    ///
+    /// Changing the mass model waits for the current frame to finish so the masses of the nodes
+    /// can be recomputed.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is `FFIError::InvalidPhysicsSettings` if the settings cannot run
+    pub fn set_settings(&self, settings: PhysicsSettings) -> Result<(), FFIError> {
+        if !settings.is_valid() {
+            return Err(FFIError::InvalidPhysicsSettings);
+        }
+
+        // Frames lock the graph before the settings, so the graph is locked first here as well
+        let mut g = if self.settings().mass_model != settings.mass_model {
+            Some(match self.graph.lock() {
+                Ok(g) => g,
+                Err(e) => e.into_inner(),
+            })
+        } else {
+            None
+        };
+        if let Some(g) = &mut g {
+            for node in g.1.values_mut() {
+                node.set_mass_model(settings.mass_model);
+            }
+        }
+
+        match self.settings.lock() {
+            Ok(mut current) => *current = settings,
+            Err(e) => *e.into_inner() = settings,
+        }
+        Ok(())
+    }
+
     pub fn graph_mut(&mut self) -> Result<&mut HashMap<String, PhysicsNode>, String> {
         match self.graph.get_mut() {
             Ok(graph) => Ok(&mut graph.1),
@@ -97,6 +240,24 @@ impl ForceDirectedGraph {
         }
     }
 
+    /// Function to copy the position of every node, sorted by cluster name
+    pub fn positions(&self) -> Vec<(String, glam::Vec3)> {
+        let g = match self.graph.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+        let mut positions: Vec<_> =
+            g.1.iter()
+                .map(|(id, node)| (id.clone(), node.get_position()))
+                .collect();
+        positions.sort_by(|a, b| a.0.cmp(&b.0));
+        positions
+    }
+
+    pub fn edges(&self) -> &[Spring] {
+        &self.edges
+    }
+
     pub fn edges_mut(&mut self) -> &mut Vec<Spring> {
         &mut self.edges
     }
@@ -114,7 +275,8 @@ impl ForceDirectedGraph {
 
         match mutex_result {
             Ok(mut g) => {
-                if g.0.force_shutdown {
+                // A converged layout stops the same way as a shutdown
+                if g.0.force_shutdown || self.converged_at().is_some() {
                     g.0.data_ready = false;
                     return false;
                 } else {
@@ -123,6 +285,7 @@ impl ForceDirectedGraph {
                         &self.edges,
                         self.max_edge_len,
                         self.scalar,
+                        self.settings().spring_constant,
                     );
 
                     g.0.data_ready = true;
@@ -141,51 +304,134 @@ impl ForceDirectedGraph {
         edges: &Vec<Spring>,
         max_edge_len: f32,
         scalar: f32,
+        spring_constant: f32,
     ) {
         for spring in edges.iter() {
-            spring.move_nodes(graph, max_edge_len, scalar);
+            spring.move_nodes(graph, max_edge_len, scalar, spring_constant);
         }
     }
 
-    pub fn accumulate_random_forces(
+    /// Function to accumulate the forces between clusters that are not joined by an edge, with
+    /// the force model of the graph
+    fn accumulate_global_forces(
+        &self,
         graph: &mut HashMap<String, PhysicsNode>,
-        clam_graph: &Graphf32,
-        tree: &Treef32,
-        max_edge_len: f32,
-        scalar: f32,
+        clusters: &[&Vertexf32],
+        tree: &ClamTree,
+        settings: &PhysicsSettings,
+    ) {
+        match settings.force_model {
+            ForceModel::RandomSprings => {
+                self.accumulate_random_forces(graph, clusters, tree, settings)
+            }
+            ForceModel::BarnesHut => barnes_hut::accumulate_repulsion_forces(
+                graph,
+                self.scalar,
+                settings.spring_constant,
+            ),
+        }
+    }
+
+    /// Function to get the hits and metric calls of the cache of distances between clusters
+    pub fn distance_cache_stats(&self) -> DistanceCacheStats {
+        match self.distance_cache.lock() {
+            Ok(cache) => cache.stats(),
+            Err(e) => e.into_inner().stats(),
+        }
+    }
+
+    fn accumulate_random_forces(
+        &self,
+        graph: &mut HashMap<String, PhysicsNode>,
+        clusters: &[&Vertexf32],
+        tree: &ClamTree,
+        settings: &PhysicsSettings,
     ) {
-        let mut rng: rand::prelude::ThreadRng = rand::thread_rng();
-        for cluster1 in clam_graph.ordered_clusters() {
-            for _ in 0..3 {
-                if let Some(cluster2) = clam_graph.ordered_clusters().iter().choose(&mut rng) {
-                    let dist = cluster1.distance_to_other(tree.data(), cluster2);
+        let mut distances = self.distance_cache.lock().unwrap();
+        let mut rng = self.rng.lock().unwrap();
+
+        for cluster1 in clusters {
+            for _ in 0..settings.random_partners {
+                if let Some(cluster2) = clusters.iter().choose(&mut *rng) {
+                    let dist = distances.distance(tree, cluster1, cluster2);
 
                     let spring = Spring::new(dist, cluster1.name(), cluster2.name(), false);
 
-                    spring.move_nodes(graph, max_edge_len, scalar);
+                    spring.move_nodes(
+                        graph,
+                        self.max_edge_len,
+                        self.scalar,
+                        settings.spring_constant,
+                    );
                 }
             }
         }
     }
 
-    pub fn apply_forces(graph: &mut HashMap<String, PhysicsNode>) {
+    pub fn apply_forces(graph: &mut HashMap<String, PhysicsNode>, settings: &PhysicsSettings) {
         for (_, value) in graph {
-            value.update_position();
+            value.update_position(settings);
         }
     }
 
     pub fn apply_forces_and_update_unity(
         graph: &mut HashMap<String, PhysicsNode>,
+        settings: &PhysicsSettings,
         updater: CBFnNodeVisitor,
     ) {
         for (key, value) in graph {
-            value.update_position();
+            value.update_position(settings);
             let baton_data = ClusterDataWrapper::from_physics(key.as_str(), value.get_position());
 
             updater(Some(baton_data.data()));
         }
     }
 
+    /// Function to write the position of every node to a buffer in the order of `node_order`
+    ///
+    /// The node at index `i` is written to `out_positions[3 * i..3 * i + 3]`. Callers check the
+    /// buffer has room for every node with `fits_positions` first.
+    fn write_positions(&self, graph: &HashMap<String, PhysicsNode>, out_positions: &mut [f32]) {
+        for (id, out) in self.order.iter().zip(out_positions.chunks_exact_mut(3)) {
+            if let Some(node) = graph.get(id) {
+                out.copy_from_slice(&node.get_position().to_array());
+            }
+        }
+    }
+
+    /// Function to check if a buffer has room for the position of every node
+    fn fits_positions(&self, out_positions: &[f32]) -> bool {
+        out_positions.len() >= 3 * self.order.len()
+    }
+
+    /// Function to get the names of the nodes in the order their positions are copied
+    pub fn node_order(&self) -> &[String] {
+        &self.order
+    }
+
+    /// Function to copy the position of every node to a buffer without waiting for the physics
+    ///
+    /// # Arguments
+    ///
+    /// * `out_positions` - Three floats for every node, in the order of `node_order`
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` that is `Ok` if the positions were copied, `BufferTooSmall` if the buffer has
+    /// no room for every node or `PhysicsNotReady` if the physics thread is computing a frame
+    pub fn copy_positions(&self, out_positions: &mut [f32]) -> FFIError {
+        if !self.fits_positions(out_positions) {
+            return FFIError::BufferTooSmall;
+        }
+        match self.graph.try_lock() {
+            Ok(g) => {
+                self.write_positions(&g.1, out_positions);
+                FFIError::Ok
+            }
+            Err(_) => FFIError::PhysicsNotReady,
+        }
+    }
+
     unsafe fn force_shutdown(&self) -> FFIError {
         debug!("trying to end sim early - force shutdown lock");
 
@@ -202,23 +448,194 @@ impl ForceDirectedGraph {
         }
     }
 
+    /// Function to hold the node of a cluster at a position while the rest of the graph moves
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The name of the cluster
+    /// * `position` - Where to hold the node
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is an `FFIError::NodeNotInGraph` if the cluster is not in the graph
+    pub fn pin_node(&self, id: &str, position: glam::Vec3) -> Result<(), FFIError> {
+        let mut g = match self.graph.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+        let node = g.1.get_mut(id).ok_or(FFIError::NodeNotInGraph)?;
+        node.pin(position);
+        Ok(())
+    }
+
+    /// Function to move a node held with `pin_node` to another position
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The name of the cluster
+    /// * `position` - Where to hold the node now
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is an `FFIError::NodeNotInGraph` if the cluster is not in the graph or
+    /// an `FFIError::NodeNotPinned` if its node is not pinned
+    pub fn move_pinned_node(&self, id: &str, position: glam::Vec3) -> Result<(), FFIError> {
+        let mut g = match self.graph.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+        let node = g.1.get_mut(id).ok_or(FFIError::NodeNotInGraph)?;
+        if !node.is_pinned() {
+            return Err(FFIError::NodeNotPinned);
+        }
+        node.pin(position);
+        Ok(())
+    }
+
+    /// Function to let the forces move a node held with `pin_node` again
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The name of the cluster
+    ///
+    /// # Returns
+    ///
+    /// A `Result` that is an `FFIError::NodeNotInGraph` if the cluster is not in the graph
+    pub fn unpin_node(&self, id: &str) -> Result<(), FFIError> {
+        let mut g = match self.graph.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+        let node = g.1.get_mut(id).ok_or(FFIError::NodeNotInGraph)?;
+        node.unpin();
+        Ok(())
+    }
+
+    /// Function to stop moving the nodes until `resume` is called, keeping their velocities
+    pub fn pause(&self) {
+        self.set_paused(true, 0);
+    }
+
+    /// Function to continue moving the nodes after `pause` or `step`
+    pub fn resume(&self) {
+        self.set_paused(false, 0);
+    }
+
+    /// Function to pause the graph, if it is not paused, and then move the nodes by a number of
+    /// frames
+    ///
+    /// # Arguments
+    ///
+    /// * `frames` - The number of frames to run, added to those still queued by earlier steps
+    pub fn step(&self, frames: u32) {
+        let mut g = match self.graph.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+        g.0.steps = if g.0.paused {
+            g.0.steps + frames
+        } else {
+            frames
+        };
+        g.0.paused = true;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        match self.graph.lock() {
+            Ok(g) => g.0.paused,
+            Err(e) => e.into_inner().0.paused,
+        }
+    }
+
+    fn set_paused(&self, paused: bool, steps: u32) {
+        let mut g = match self.graph.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+        g.0.paused = paused;
+        g.0.steps = steps;
+    }
+
+    /// Function to let the graph run again from its current positions once its thread has stopped
+    ///
+    /// The graph is unpaused and its convergence is forgotten, so a new thread runs until it
+    /// converges again or runs out of iterations.
+    pub fn restart(&self) {
+        let mut g = match self.graph.lock() {
+            Ok(g) => g,
+            Err(e) => e.into_inner(),
+        };
+        g.0 = Status::new();
+        match self.energy.lock() {
+            Ok(mut energy) => energy.restart(),
+            Err(e) => e.into_inner().restart(),
+        }
+    }
+
+    pub fn max_iters(&self) -> i32 {
+        self.max_iters
+    }
+
     unsafe fn try_update_unity(
         &self,
-        clam_graph: &Graphf32,
-        tree: &Treef32,
+        clusters: &[&Vertexf32],
+        tree: &ClamTree,
         updater: CBFnNodeVisitor,
+    ) -> FFIError {
+        self.try_apply_frame(clusters, tree, |graph, settings| {
+            Self::apply_forces_and_update_unity(graph, settings, updater)
+        })
+    }
+
+    fn try_update_positions(
+        &self,
+        clusters: &[&Vertexf32],
+        tree: &ClamTree,
+        out_positions: &mut [f32],
+    ) -> FFIError {
+        // Checked before the frame is applied, so a frame is not spent without reporting it
+        if !self.fits_positions(out_positions) {
+            return FFIError::BufferTooSmall;
+        }
+        self.try_apply_frame(clusters, tree, |graph, settings| {
+            Self::apply_forces(graph, settings);
+            self.write_positions(graph, out_positions);
+        })
+    }
+
+    /// Function to finish the frame the physics thread has computed, if it has, and hand it back
+    ///
+    /// A frame is only finished once the physics thread has added its edge forces, so a paused
+    /// graph does not spend a step on a frame without them
+    ///
+    /// # Arguments
+    ///
+    /// * `clusters` - The clusters of the graph
+    /// * `tree` - The tree the clusters are from
+    /// * `apply` - Moves the nodes by the forces of the frame and reports their positions
+    fn try_apply_frame(
+        &self,
+        clusters: &[&Vertexf32],
+        tree: &ClamTree,
+        apply: impl FnOnce(&mut HashMap<String, PhysicsNode>, &PhysicsSettings),
     ) -> FFIError {
         match self.graph.try_lock() {
             Ok(mut g) => {
-                Self::accumulate_random_forces(
-                    &mut g.1,
-                    clam_graph,
-                    tree,
-                    self.max_edge_len,
-                    self.scalar,
-                );
+                if !g.0.data_ready {
+                    return FFIError::PhysicsNotReady;
+                }
+                if g.0.paused {
+                    if g.0.steps == 0 {
+                        return FFIError::PhysicsPaused;
+                    }
+                    g.0.steps -= 1;
+                }
 
-                Self::apply_forces_and_update_unity(&mut g.1, updater);
+                let settings = self.settings();
+                self.accumulate_global_forces(&mut g.1, clusters, tree, &settings);
+
+                apply(&mut g.1, &settings);
+                self.record_energy(&g.1, &settings);
 
                 g.0.data_ready = false;
                 self.cond_var.notify_one();
@@ -248,8 +665,8 @@ impl ForceDirectedGraph {
     }
 }
 
-pub fn produce_computations(force_directed_graph: &ForceDirectedGraph) {
-    for _ in 0..force_directed_graph.max_iters {
+pub fn produce_computations(force_directed_graph: &ForceDirectedGraph, iterations: i32) {
+    for _ in 0..iterations {
         // returns false if being forced to terminate mid - simulation
         if !force_directed_graph.compute_next_frame() {
             return;
@@ -259,19 +676,28 @@ pub fn produce_computations(force_directed_graph: &ForceDirectedGraph) {
 
 pub unsafe fn try_update_unity(
     force_directed_graph: &ForceDirectedGraph,
-    clam_graph: &Graphf32,
-    tree: &Treef32,
+    clusters: &[&Vertexf32],
+    tree: &ClamTree,
     updater: CBFnNodeVisitor,
 ) -> FFIError {
-    force_directed_graph.try_update_unity(clam_graph, tree, updater)
+    force_directed_graph.try_update_unity(clusters, tree, updater)
+}
+
+pub fn try_update_positions(
+    force_directed_graph: &ForceDirectedGraph,
+    clusters: &[&Vertexf32],
+    tree: &ClamTree,
+    out_positions: &mut [f32],
+) -> FFIError {
+    force_directed_graph.try_update_positions(clusters, tree, out_positions)
 }
 
 pub unsafe fn force_shutdown(force_directed_graph: &ForceDirectedGraph) -> FFIError {
     force_directed_graph.force_shutdown()
 }
 
-pub fn init_unity_edges(force_directed_graph: &ForceDirectedGraph, init_edges: CBFnNodeVisitorMut) {
-    for edge in &force_directed_graph.edges {
+pub fn init_unity_edges(edges: &[Spring], init_edges: CBFnNodeVisitorMut) {
+    for edge in edges {
         let mut data_wrapper = ClusterDataWrapper::default();
         let (id1, id2) = edge.get_node_ids();
         data_wrapper.data_mut().set_id(id1.clone());
@@ -283,3 +709,40 @@ pub fn init_unity_edges(force_directed_graph: &ForceDirectedGraph, init_edges: C
         init_edges(Some(&mut data_wrapper.data_mut()));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_positions() {
+        let graph: HashMap<String, PhysicsNode> = [("0-2", glam::Vec3::X), ("2-1", glam::Vec3::Y)]
+            .into_iter()
+            .map(|(id, position)| (id.to_string(), PhysicsNode::at(position)))
+            .collect();
+        let force_directed_graph = ForceDirectedGraph::new(
+            graph,
+            Vec::new(),
+            1.0,
+            PhysicsSettings::default(),
+            DistanceCache::new(&[]),
+            10,
+            Some(1),
+        );
+
+        // A buffer without room for every node is refused rather than partly written
+        let mut short = [0.0; 5];
+        assert_eq!(
+            force_directed_graph.copy_positions(&mut short),
+            FFIError::BufferTooSmall
+        );
+        assert_eq!(short, [0.0; 5]);
+
+        let mut positions = [0.0; 6];
+        assert_eq!(
+            force_directed_graph.copy_positions(&mut positions),
+            FFIError::Ok
+        );
+        assert_eq!(positions, [1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+    }
+}
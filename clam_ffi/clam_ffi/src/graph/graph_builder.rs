@@ -5,7 +5,7 @@ use std::{
 };
 
 use abd_clam::Cluster;
-use rand::{seq::SliceRandom, Rng};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
 
 use crate::{
     debug,
@@ -13,16 +13,20 @@ use crate::{
     graph,
     handle::handle::Handle,
     utils::{
+        clam_tree::ClamTree,
         error::FFIError,
-        types::{Vertexf32, DataSetf32, Graphf32, Treef32},
+        helpers,
+        types::{Graphf32, Vertexf32},
     },
 };
 
 type Edge = (String, String, f32, bool);
 
 use super::{
+    distance_cache::DistanceCache,
     force_directed_graph::ForceDirectedGraph,
     physics_node::PhysicsNode,
+    physics_settings::PhysicsSettings,
     spring::{self, Spring},
 };
 
@@ -30,9 +34,15 @@ fn get_k_key_clusters<'a>(
     clam_graph: &'a Graphf32,
     component: &'a HashSet<&'a Vertexf32>,
     k: usize,
+    rng: &mut StdRng,
 ) -> Option<Vec<&'a Vertexf32>> {
     let mut key_clusters: Vec<&Vertexf32> = Vec::new();
-    let key_cluster = component.iter().max_by(|x, y| {
+
+    // Sort the component so the choices below do not depend on the order of the set
+    let mut comp: Vec<&Vertexf32> = component.iter().map(|x| *x).collect();
+    comp.sort_by_key(|c| (c.offset(), c.cardinality()));
+
+    let key_cluster = comp.iter().copied().max_by(|x, y| {
         clam_graph
             .vertex_degree(x)
             .cmp(&clam_graph.vertex_degree(y))
@@ -41,14 +51,11 @@ fn get_k_key_clusters<'a>(
     if let Some(kc) = key_cluster {
         key_clusters.push(kc);
 
-        let mut comp: Vec<&Vertexf32> = component.iter().map(|x| *x).collect();
-
-        let mut rng = rand::thread_rng();
-        let (shuffled, _) = comp.partial_shuffle(&mut rng, k + 1);
+        let (shuffled, _) = comp.partial_shuffle(rng, k + 1);
 
         for c in shuffled {
             if key_clusters.len() < k {
-                if c != kc {
+                if *c != kc {
                     key_clusters.push(c);
                 }
             } else {
@@ -63,30 +70,38 @@ fn get_k_key_clusters<'a>(
 fn cross_pollinate_components<'a>(
     key_clusters1: &Vec<&'a Vertexf32>,
     key_clusters2: &Vec<&'a Vertexf32>,
-    data: &DataSetf32,
+    tree: &ClamTree,
     edges: &mut Vec<Spring>,
 ) {
     for c1 in key_clusters1.iter() {
         for c2 in key_clusters2.iter() {
-            let spring = Spring::new(c1.distance_to_other(data, c2), c1.name(), c2.name(), false);
+            let spring = Spring::new(tree.cluster_distance(c1, c2), c1.name(), c2.name(), false);
             edges.push(spring);
         }
     }
 }
 
 fn create_intercomponent_edges(
-    data: &DataSetf32,
+    tree: &ClamTree,
     clam_graph: &Graphf32,
     edges: &mut Vec<Spring>,
     k: usize,
+    rng: &mut StdRng,
 ) {
-    let component_clusters = clam_graph.find_component_clusters();
+    // Visit the components in the order of their first cluster so the same seed picks the same keys
+    let mut component_clusters: Vec<_> = clam_graph.find_component_clusters().iter().collect();
+    component_clusters.sort_by_key(|component| {
+        component
+            .iter()
+            .map(|c| (c.offset(), c.cardinality()))
+            .min()
+    });
 
     for (i, component) in component_clusters.iter().enumerate() {
-        if let Some(key_clusters) = get_k_key_clusters(clam_graph, component, k) {
+        if let Some(key_clusters) = get_k_key_clusters(clam_graph, component, k, rng) {
             for component2 in component_clusters.iter().skip(i + 1) {
-                if let Some(key_clusters2) = get_k_key_clusters(clam_graph, component2, k) {
-                    cross_pollinate_components(&key_clusters, &key_clusters2, data, edges)
+                if let Some(key_clusters2) = get_k_key_clusters(clam_graph, component2, k, rng) {
+                    cross_pollinate_components(&key_clusters, &key_clusters2, tree, edges)
                 }
             }
         }
@@ -97,8 +112,12 @@ pub fn build_force_directed_graph_async(
     // cluster_data_arr: &[ClusterData],
     handle: &Handle,
     scalar: f32,
+    settings: PhysicsSettings,
     max_iters: i32,
 ) -> Result<(JoinHandle<()>, Arc<ForceDirectedGraph>), FFIError> {
+    if !settings.is_valid() {
+        return Err(FFIError::InvalidPhysicsSettings);
+    }
     if let Some(tree) = handle.tree() {
         if let Some(clam_graph) = handle.clam_graph() {
             // let mut graph: HashMap<String, PhysicsNode> = HashMap::new();
@@ -123,35 +142,57 @@ pub fn build_force_directed_graph_async(
             // create_intercomponent_edges(tree.data(), clam_graph, &mut springs, 3);
 
             let force_directed_graph = Arc::new(build_force_directed_graph(
-                tree, clam_graph, scalar, max_iters,
+                tree,
+                &clam_graph,
+                scalar,
+                settings,
+                max_iters,
+                handle.seed(),
             ));
 
-            let b = force_directed_graph.clone();
-            let p = thread::spawn(move || {
-                graph::force_directed_graph::produce_computations(&b);
-            });
-            return Ok((p, force_directed_graph.clone()));
+            let p = spawn_physics_thread(force_directed_graph.clone(), max_iters);
+            return Ok((p, force_directed_graph));
         }
     }
 
     Err(FFIError::GraphBuildFailed)
 }
 
+/// Function to run the physics of a force-directed graph on a new thread
+///
+/// # Arguments
+///
+/// * `force_directed_graph` - The graph to move
+/// * `iterations` - The most frames to run, fewer if the graph converges or is shut down
+pub fn spawn_physics_thread(
+    force_directed_graph: Arc<ForceDirectedGraph>,
+    iterations: i32,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        graph::force_directed_graph::produce_computations(&force_directed_graph, iterations);
+    })
+}
+
 pub fn build_force_directed_graph<'a>(
     // cluster_data_arr: &[ClusterData],
-    tree: &'a Treef32,
+    tree: &'a ClamTree,
     clam_graph: &'a Graphf32,
     scalar: f32,
+    settings: PhysicsSettings,
     max_iters: i32,
+    seed: Option<u64>,
 ) -> ForceDirectedGraph {
     let mut graph: HashMap<String, PhysicsNode> = HashMap::new();
-    let mut rng = rand::thread_rng();
+    let mut rng = helpers::seeded_rng(seed);
 
     for c in clam_graph.ordered_clusters().iter() {
         let x: f32 = rng.gen_range(0.0..=100.0);
         let y: f32 = rng.gen_range(0.0..=100.0);
         let z: f32 = rng.gen_range(0.0..=100.0);
-        graph.insert(c.name(), PhysicsNode::new(glam::Vec3::new(x, y, z), c));
+        graph.insert(
+            c.name(),
+            PhysicsNode::new(glam::Vec3::new(x, y, z), c, settings.mass_model),
+        );
     }
     let mut springs = Vec::new();
     for e in clam_graph.edges() {
@@ -162,8 +203,19 @@ pub fn build_force_directed_graph<'a>(
             true,
         ));
     }
-
-    create_intercomponent_edges(tree.data(), clam_graph, &mut springs, 3);
-
-    ForceDirectedGraph::new(graph, springs, scalar, max_iters)
+    // The edges come out of a set, so sort them to apply the spring forces in the same order
+    springs.sort_by(|a, b| a.get_node_ids().cmp(&b.get_node_ids()));
+
+    create_intercomponent_edges(tree, clam_graph, &mut springs, 3, &mut rng);
+
+    let physics_seed = seed.map(|_| rng.gen());
+    ForceDirectedGraph::new(
+        graph,
+        springs,
+        scalar,
+        settings,
+        DistanceCache::new(clam_graph.ordered_clusters()),
+        max_iters,
+        physics_seed,
+    )
 }
@@ -0,0 +1,481 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use abd_clam::graph::{Edge, Graph};
+use abd_clam::Cluster;
+
+use crate::debug;
+use crate::utils::clam_tree::ClamTree;
+use crate::utils::error::FFIError;
+use crate::utils::helpers;
+use crate::utils::scoring_functions::{self, enum_to_function, ScoringFunction};
+use crate::utils::types::{Graphf32, Vertexf32};
+
+/// How the clusters of a graph are chosen from the tree
+///
+/// Every mode other than `Scorer` selects the clusters itself and connects two clusters with an
+/// edge when their centers are no further apart than the sum of their radii.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionMode {
+    /// The clusters picked by a pretrained CHAODA scorer, no shallower than the min depth
+    Scorer(ScoringFunction, i32),
+    /// Every cluster at a depth, and the leaves above it
+    Depth(usize),
+    /// The cut of the tree with a target number of clusters, made by splitting the cluster with
+    /// the largest radius until there are enough clusters or only leaves are left
+    LeafCut(usize),
+    /// The shallowest clusters with at most the max radius and max local fractal dimension, or
+    /// leaves. Thresholds that are zero or negative are not checked
+    Threshold { max_radius: f32, max_lfd: f32 },
+    /// Clusters given by offset and cardinality
+    Ids(Vec<(usize, usize)>),
+}
+
+impl SelectionMode {
+    /// Function to parse a mode written by its `Display` implementation
+    ///
+    /// The forms are `scorer <scoring function> <min depth>`, `depth <depth>`,
+    /// `leaf_cut <cluster count>`, `threshold <max radius> <max lfd>` and `ids <id> <id> ...` with
+    /// ids written as `offset-cardinality`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = text.split_whitespace().collect();
+        let parse_value = |i: usize| -> Result<&str, String> {
+            fields
+                .get(i)
+                .copied()
+                .ok_or_else(|| format!("missing value in selection '{}'", text))
+        };
+        let invalid = |value: &str| format!("invalid value {} in selection '{}'", value, text);
+
+        let (mode, len) = match fields.first().copied() {
+            Some("scorer") => {
+                let name = parse_value(1)?;
+                let scoring_function = scoring_functions::string_to_enum(name)
+                    .map_err(|_| format!("unknown scoring function {}", name))?;
+                let min_depth = parse_value(2)?;
+                let min_depth = min_depth.parse().map_err(|_| invalid(min_depth))?;
+                (SelectionMode::Scorer(scoring_function, min_depth), 3)
+            }
+            Some("depth") => {
+                let depth = parse_value(1)?;
+                (
+                    SelectionMode::Depth(depth.parse().map_err(|_| invalid(depth))?),
+                    2,
+                )
+            }
+            Some("leaf_cut") => {
+                let count = parse_value(1)?;
+                (
+                    SelectionMode::LeafCut(count.parse().map_err(|_| invalid(count))?),
+                    2,
+                )
+            }
+            Some("threshold") => {
+                let (max_radius, max_lfd) = (parse_value(1)?, parse_value(2)?);
+                (
+                    SelectionMode::Threshold {
+                        max_radius: max_radius.parse().map_err(|_| invalid(max_radius))?,
+                        max_lfd: max_lfd.parse().map_err(|_| invalid(max_lfd))?,
+                    },
+                    3,
+                )
+            }
+            Some("ids") => {
+                let ids = fields[1..]
+                    .iter()
+                    .map(|id| helpers::parse_cluster_name(id).map_err(|_| invalid(id)))
+                    .collect::<Result<_, _>>()?;
+                (SelectionMode::Ids(ids), fields.len())
+            }
+            _ => return Err(format!("unknown selection '{}'", text)),
+        };
+
+        if fields.len() != len {
+            return Err(format!("too many values in selection '{}'", text));
+        }
+        Ok(mode)
+    }
+}
+
+impl fmt::Display for SelectionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionMode::Scorer(scoring_function, min_depth) => write!(
+                f,
+                "scorer {} {}",
+                scoring_functions::enum_to_string(scoring_function).map_err(|_| fmt::Error)?,
+                min_depth
+            ),
+            SelectionMode::Depth(depth) => write!(f, "depth {}", depth),
+            SelectionMode::LeafCut(count) => write!(f, "leaf_cut {}", count),
+            SelectionMode::Threshold {
+                max_radius,
+                max_lfd,
+            } => write!(f, "threshold {} {}", max_radius, max_lfd),
+            SelectionMode::Ids(ids) => {
+                write!(f, "ids")?;
+                for (offset, cardinality) in ids {
+                    write!(f, " {}-{}", offset, cardinality)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The clusters and edges of a graph selected from a tree, stored by cluster id
+///
+/// A `Graph` borrows its clusters from the tree, so the handle keeps a selection and its clusters
+/// instead and builds the `Graph` from them whenever one is needed. The selection owns all of its data, so it can be
+/// kept, cloned and replaced independently of the tree it was selected from.
+#[derive(Debug, Clone)]
+pub struct GraphSelection {
+    mode: SelectionMode,
+    /// The offset and cardinality of every cluster, in the order of `Graph::ordered_clusters`
+    clusters: Vec<(usize, usize)>,
+    /// The edges as indices into `clusters` and their distance
+    edges: Vec<(usize, usize, f32)>,
+}
+
+impl GraphSelection {
+    /// Function to select a graph from a tree with a CHAODA scorer
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The tree to select the graph from
+    /// * `scoring_function` - The scoring function to use
+    /// * `min_depth` - The minimum depth of the graph
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the selection or an `FFIError` if the graph could not be built
+    pub fn select(
+        tree: &ClamTree,
+        scoring_function: ScoringFunction,
+        min_depth: i32,
+    ) -> Result<Self, FFIError> {
+        Self::select_with(tree, SelectionMode::Scorer(scoring_function, min_depth))
+    }
+
+    /// Function to select a graph from a tree
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The tree to select the graph from
+    /// * `mode` - How to choose the clusters
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the selection, `FFIError::InvalidStringPassed` if an explicit cluster
+    /// id is not in the tree, or another `FFIError` if the graph could not be built
+    pub fn select_with(tree: &ClamTree, mode: SelectionMode) -> Result<Self, FFIError> {
+        let mut clusters = match &mode {
+            SelectionMode::Scorer(scoring_function, min_depth) => {
+                let scorer = enum_to_function(scoring_function)?;
+                let graph = tree
+                    .build_graph(&scorer, (*min_depth).max(0) as usize)
+                    .map_err(|e| {
+                        debug!("{}", e);
+                        FFIError::GraphBuildFailed
+                    })?;
+                return Ok(Self::from_graph(&graph, mode));
+            }
+            SelectionMode::Depth(depth) => cut(tree.root(), |c| c.depth() >= *depth),
+            SelectionMode::LeafCut(count) => leaf_cut(tree.root(), *count),
+            SelectionMode::Threshold {
+                max_radius,
+                max_lfd,
+            } => cut(tree.root(), |c| {
+                (*max_radius <= 0.0 || c.radius() <= *max_radius)
+                    && (*max_lfd <= 0.0 || c.lfd() <= *max_lfd as f64)
+            }),
+            SelectionMode::Ids(ids) => ids
+                .iter()
+                .map(|&(offset, cardinality)| {
+                    tree.get_cluster(offset, cardinality).ok_or_else(|| {
+                        debug!("cluster {}-{} is not in the tree", offset, cardinality);
+                        FFIError::InvalidStringPassed
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        clusters.sort_by_key(|c| (c.offset(), c.cardinality()));
+        clusters.dedup_by_key(|c| (c.offset(), c.cardinality()));
+
+        // Connect clusters whose volumes overlap, as CLAM does for the graphs it selects
+        let edges = overlapping_edges(tree, &clusters);
+
+        Ok(GraphSelection {
+            mode,
+            clusters: clusters
+                .iter()
+                .map(|c| (c.offset(), c.cardinality()))
+                .collect(),
+            edges,
+        })
+    }
+
+    /// Function to copy the clusters and edges out of a graph
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - The graph to copy
+    /// * `mode` - How the clusters of the graph were chosen
+    pub fn from_graph(graph: &Graphf32, mode: SelectionMode) -> Self {
+        let clusters: Vec<(usize, usize)> = graph
+            .ordered_clusters()
+            .iter()
+            .map(|c| (c.offset(), c.cardinality()))
+            .collect();
+        let indices: HashMap<(usize, usize), usize> = clusters
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| (id, i))
+            .collect();
+
+        let index_of = |c: &Vertexf32| indices[&(c.offset(), c.cardinality())];
+        let mut edges: Vec<(usize, usize, f32)> = graph
+            .edges()
+            .iter()
+            .map(|e| (index_of(e.left()), index_of(e.right()), e.distance()))
+            .collect();
+        edges.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+        GraphSelection {
+            mode,
+            clusters,
+            edges,
+        }
+    }
+
+    /// Function to get how the clusters were chosen
+    pub fn mode(&self) -> &SelectionMode {
+        &self.mode
+    }
+
+    /// Function to get the offset and cardinality of every selected cluster
+    pub fn cluster_ids(&self) -> &[(usize, usize)] {
+        &self.clusters
+    }
+
+    pub fn vertex_cardinality(&self) -> usize {
+        self.clusters.len()
+    }
+
+    pub fn edge_cardinality(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Function to count the connected components of the graph from its edges, without building it
+    pub fn num_components(&self) -> usize {
+        fn root(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+
+        let mut parents: Vec<usize> = (0..self.clusters.len()).collect();
+        let mut components = self.clusters.len();
+        for &(left, right, _) in &self.edges {
+            let (left, right) = (root(&mut parents, left), root(&mut parents, right));
+            if left != right {
+                parents[left] = right;
+                components -= 1;
+            }
+        }
+        components
+    }
+
+    /// Function to look up the selected clusters in the tree they were selected from
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the clusters in selection order or `FFIError::GraphBuildFailed` if the
+    /// tree does not hold one of them
+    pub fn clusters<'t>(&self, tree: &'t ClamTree) -> Result<Vec<&'t Vertexf32>, FFIError> {
+        self.clusters
+            .iter()
+            .map(|&(offset, cardinality)| {
+                tree.get_cluster(offset, cardinality).ok_or_else(|| {
+                    debug!("cluster {}-{} is not in the tree", offset, cardinality);
+                    FFIError::GraphBuildFailed
+                })
+            })
+            .collect()
+    }
+
+    /// Function to build the graph of the selection over its clusters
+    ///
+    /// No distances are computed, so this is cheap compared to selecting the graph again
+    ///
+    /// # Arguments
+    ///
+    /// * `clusters` - The clusters of the selection, from `clusters`
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the graph or `FFIError::GraphBuildFailed` if the clusters are not
+    /// those of the selection
+    pub fn graph<'t>(&self, clusters: &[&'t Vertexf32]) -> Result<Graphf32<'t>, FFIError> {
+        if clusters.len() != self.clusters.len() {
+            return Err(FFIError::GraphBuildFailed);
+        }
+        let edges = self
+            .edges
+            .iter()
+            .map(|&(left, right, distance)| Edge::new(clusters[left], clusters[right], distance))
+            .collect();
+
+        Graph::new(clusters.iter().copied().collect(), edges).map_err(|e| {
+            debug!("{}", e);
+            FFIError::GraphBuildFailed
+        })
+    }
+}
+
+/// Function to find every pair of clusters whose centers are no further apart than the sum of
+/// their radii
+///
+/// Each cluster searches the tree for the others rather than measuring its distance to all of them.
+/// A cluster of the tree is only opened if it holds a selected cluster, and is passed over with
+/// everything under it once its ball is too far away to hold a center that could be close enough:
+/// a selected cluster `s` under a cluster `c` has `d(q, s) >= d(q, c) - r(c)`, so if
+/// `d(q, c) > r(q) + r(c) + max r` then `d(q, s) > r(q) + r(s)`.
+///
+/// # Arguments
+///
+/// * `tree` - The tree the clusters are from
+/// * `clusters` - The clusters, in selection order
+///
+/// # Returns
+///
+/// The edges as indices into `clusters`, with the lower index first, and their distance
+fn overlapping_edges(tree: &ClamTree, clusters: &[&Vertexf32]) -> Vec<(usize, usize, f32)> {
+    let id = |c: &Vertexf32| (c.offset(), c.cardinality());
+    let indices: HashMap<(usize, usize), usize> = clusters
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| (id(c), i))
+        .collect();
+    let max_radius = clusters.iter().map(|c| c.radius()).fold(0.0, f32::max);
+
+    // The clusters on the path from the root to any selected cluster
+    let mut holders = HashSet::new();
+    for &selected in clusters {
+        let mut cluster = tree.root();
+        loop {
+            holders.insert(id(cluster));
+            match cluster.children() {
+                Some([left, right]) if id(cluster) != id(selected) => {
+                    cluster = if selected.offset() < left.offset() + left.cardinality() {
+                        left
+                    } else {
+                        right
+                    };
+                }
+                _ => break,
+            }
+        }
+    }
+
+    let mut edges = Vec::new();
+    for (i, &query) in clusters.iter().enumerate() {
+        let mut stack = vec![tree.root()];
+        while let Some(cluster) = stack.pop() {
+            if !holders.contains(&id(cluster)) {
+                continue;
+            }
+            let distance = tree.cluster_distance(query, cluster);
+            if distance > query.radius() + cluster.radius() + max_radius {
+                continue;
+            }
+
+            if let Some(&j) = indices.get(&id(cluster)) {
+                if j > i && distance <= query.radius() + cluster.radius() {
+                    edges.push((i, j, distance));
+                }
+            }
+            if let Some([left, right]) = cluster.children() {
+                stack.push(right);
+                stack.push(left);
+            }
+        }
+    }
+    edges.sort_by_key(|&(left, right, _)| (left, right));
+    edges
+}
+
+/// Function to cut the tree at the shallowest clusters that are selected, or at the leaves
+fn cut(root: &Vertexf32, is_selected: impl Fn(&Vertexf32) -> bool) -> Vec<&Vertexf32> {
+    let mut selected = Vec::new();
+    let mut stack = vec![root];
+    while let Some(cluster) = stack.pop() {
+        match cluster.children() {
+            Some([left, right]) if !is_selected(cluster) => {
+                stack.push(right);
+                stack.push(left);
+            }
+            _ => selected.push(cluster),
+        }
+    }
+    selected
+}
+
+/// Function to cut the tree into `count` clusters by splitting the cluster with the largest radius
+fn leaf_cut(root: &Vertexf32, count: usize) -> Vec<&Vertexf32> {
+    let mut selected = vec![root];
+    while selected.len() < count {
+        let widest = selected
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| !c.is_leaf())
+            .max_by(|(_, a), (_, b)| a.radius().total_cmp(&b.radius()))
+            .map(|(i, _)| i);
+
+        match widest.and_then(|i| selected.swap_remove(i).children()) {
+            Some([left, right]) => selected.extend([left, right]),
+            None => break,
+        }
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_mode_round_trip() {
+        for text in [
+            "scorer lr_euclidean_cc 4",
+            "depth 6",
+            "leaf_cut 200",
+            "threshold 0.5 0",
+            "ids 0-10 10-5",
+        ] {
+            let mode = SelectionMode::parse(text).unwrap();
+            assert_eq!(mode.to_string(), text);
+        }
+
+        assert_eq!(
+            SelectionMode::parse("ids 0-10 10-5").unwrap(),
+            SelectionMode::Ids(vec![(0, 10), (10, 5)])
+        );
+        assert!(SelectionMode::parse("depth").is_err());
+        assert!(SelectionMode::parse("depth 4 5").is_err());
+        assert!(SelectionMode::parse("ids 0_10").is_err());
+        assert!(SelectionMode::parse("scorer nope 4").is_err());
+    }
+
+    #[test]
+    fn test_num_components() {
+        let selection = GraphSelection {
+            mode: SelectionMode::LeafCut(5),
+            clusters: vec![(0, 1), (1, 1), (2, 1), (3, 1), (4, 1)],
+            edges: vec![(0, 1, 1.0), (1, 2, 1.0), (0, 2, 1.0), (3, 4, 1.0)],
+        };
+        assert_eq!(selection.num_components(), 2);
+    }
+}
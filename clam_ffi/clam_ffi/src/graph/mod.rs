@@ -1,6 +1,11 @@
+pub mod barnes_hut;
+pub mod distance_cache;
+pub mod energy;
 pub mod entry;
 pub mod force_directed_graph;
 pub mod graph_builder;
+pub mod graph_selection;
 pub mod helpers;
 pub mod physics_node;
+pub mod physics_settings;
 pub mod spring;
@@ -1,5 +1,6 @@
 use abd_clam::Cluster;
 
+use super::physics_settings::{MassModel, PhysicsSettings};
 use crate::graph;
 use crate::utils::types::Vertexf32;
 
@@ -14,22 +15,36 @@ use crate::utils::types::Vertexf32;
 
 pub struct PhysicsNode {
     position: glam::Vec3,
-    friction: f32,
-    max_speed: f32,
     velocity: glam::Vec3,
     acceleration: glam::Vec3,
+    cardinality: usize,
     mass: f32,
+    /// A pinned node stays where it was put, but still pulls on the nodes it is connected to
+    pinned: bool,
 }
 
 impl PhysicsNode {
-    pub fn new(pos: glam::Vec3, cluster: &Vertexf32) -> Self {
+    pub fn new(pos: glam::Vec3, cluster: &Vertexf32, mass_model: MassModel) -> Self {
         PhysicsNode {
             position: pos,
-            friction: 0.98,
-            max_speed: 5.,
             velocity: glam::Vec3::new(0., 0., 0.),
             acceleration: glam::Vec3::new(0., 0., 0.),
-            mass: cluster.cardinality() as f32,
+            cardinality: cluster.cardinality(),
+            mass: mass_model.mass(cluster.cardinality()),
+            pinned: false,
+        }
+    }
+
+    /// Function to create a node of a single point at a position
+    #[cfg(test)]
+    pub(crate) fn at(position: glam::Vec3) -> Self {
+        PhysicsNode {
+            position,
+            velocity: glam::Vec3::ZERO,
+            acceleration: glam::Vec3::ZERO,
+            cardinality: 1,
+            mass: 1.0,
+            pinned: false,
         }
     }
 
@@ -37,6 +52,11 @@ impl PhysicsNode {
         self.mass
     }
 
+    /// Function to recompute the mass of the node with another mass model
+    pub fn set_mass_model(&mut self, mass_model: MassModel) {
+        self.mass = mass_model.mass(self.cardinality);
+    }
+
     // F = M * A
     //updates acceleration of node
     pub fn accelerate(&mut self, force: glam::Vec3) {
@@ -44,23 +64,81 @@ impl PhysicsNode {
         self.acceleration += force / self.mass();
     }
 
+    pub fn kinetic_energy(&self) -> f32 {
+        0.5 * self.mass * self.velocity.length_squared()
+    }
+
     pub fn get_position(&self) -> glam::Vec3 {
         self.position
     }
 
+    /// Function to hold the node at a position, where forces no longer move it
+    pub fn pin(&mut self, position: glam::Vec3) {
+        self.position = position;
+        self.velocity = glam::Vec3::ZERO;
+        self.acceleration = glam::Vec3::ZERO;
+        self.pinned = true;
+    }
+
+    /// Function to let forces move the node again, starting from rest
+    pub fn unpin(&mut self) {
+        self.pinned = false;
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
     //applies acceleration to velocity, applies velocity of node's position then updates sphere object on canvas
-    pub fn update_position(&mut self) {
-        self.velocity += self.acceleration;
-        self.velocity *= self.friction; //reduce velocity by applying friction
+    pub fn update_position(&mut self, settings: &PhysicsSettings) {
+        if self.pinned {
+            self.acceleration = glam::Vec3::ZERO;
+            return;
+        }
+
+        self.velocity += self.acceleration * settings.timestep;
+        self.velocity *= settings.friction.powf(settings.timestep); //reduce velocity by applying friction
 
         //if current velocity > max_speed, set velocity to max speed (to prevent extreme rubber banding in some graphs)
-        if graph::helpers::get_magnitude(self.velocity) > self.max_speed {
-            self.velocity = graph::helpers::set_magnitude(self.velocity, self.max_speed);
+        if graph::helpers::get_magnitude(self.velocity) > settings.max_speed {
+            self.velocity = graph::helpers::set_magnitude(self.velocity, settings.max_speed);
         }
 
-        self.position += self.velocity;
+        self.position += self.velocity * settings.timestep;
         self.acceleration.x = 0.;
         self.acceleration.y = 0.;
         self.acceleration.z = 0.;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_node() {
+        let mut node = PhysicsNode {
+            position: glam::Vec3::ZERO,
+            velocity: glam::Vec3::ZERO,
+            acceleration: glam::Vec3::ZERO,
+            cardinality: 1,
+            mass: 1.0,
+            pinned: false,
+        };
+        let settings = PhysicsSettings::default();
+
+        node.pin(glam::Vec3::ONE);
+        node.accelerate(glam::Vec3::X);
+        node.update_position(&settings);
+        assert_eq!(node.get_position(), glam::Vec3::ONE);
+        assert_eq!(node.kinetic_energy(), 0.0);
+
+        // The force on a pinned node is dropped rather than applied once it is unpinned
+        node.unpin();
+        node.update_position(&settings);
+        assert_eq!(node.get_position(), glam::Vec3::ONE);
+        node.accelerate(glam::Vec3::X);
+        node.update_position(&settings);
+        assert!(node.get_position().x > 1.0);
+    }
+}
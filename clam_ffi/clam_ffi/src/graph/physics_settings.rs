@@ -0,0 +1,91 @@
+use super::force_directed_graph::ForceModel;
+
+/// How the mass of the node of a cluster is chosen. Heavier nodes are moved less by the same force
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum MassModel {
+    /// The number of instances in the cluster
+    Cardinality,
+    /// One plus the natural log of the number of instances, so large clusters still move
+    LogCardinality,
+    /// Every node has a mass of one
+    Uniform,
+}
+
+impl MassModel {
+    /// Function to get a mass model from its name, `cardinality`, `log_cardinality` or `uniform`
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "cardinality" => Ok(MassModel::Cardinality),
+            "log_cardinality" => Ok(MassModel::LogCardinality),
+            "uniform" => Ok(MassModel::Uniform),
+            _ => Err(format!("unknown mass model {}", name)),
+        }
+    }
+
+    /// Function to get the mass of the node of a cluster with the given cardinality
+    pub fn mass(&self, cardinality: usize) -> f32 {
+        match self {
+            MassModel::Cardinality => cardinality as f32,
+            MassModel::LogCardinality => 1.0 + (cardinality as f32).ln(),
+            MassModel::Uniform => 1.0,
+        }
+    }
+}
+
+/// The parameters of the physics of a force-directed graph
+///
+/// They can be changed while the physics is running, and the next frame uses the new values.
+#[repr(C)]
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct PhysicsSettings {
+    /// The fraction of its velocity a node keeps every unit of time
+    pub friction: f32,
+    /// The fastest a node can move, in units of the layout per unit of time
+    pub max_speed: f32,
+    /// The stiffness of the springs, and of the repulsion of `ForceModel::BarnesHut`
+    pub spring_constant: f32,
+    /// The number of random clusters each cluster is pulled towards every frame by
+    /// `ForceModel::RandomSprings`
+    pub random_partners: i32,
+    pub mass_model: MassModel,
+    pub force_model: ForceModel,
+    /// The time that passes every frame
+    pub timestep: f32,
+    /// The simulation stops once its energy changes by at most this fraction of itself for
+    /// `energy::SETTLED_FRAMES` frames in a row. Zero runs every iteration
+    pub convergence_threshold: f32,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        PhysicsSettings {
+            friction: 0.98,
+            max_speed: 5.0,
+            spring_constant: 0.005,
+            random_partners: 3,
+            mass_model: MassModel::Cardinality,
+            force_model: ForceModel::RandomSprings,
+            timestep: 1.0,
+            convergence_threshold: 1e-4,
+        }
+    }
+}
+
+impl PhysicsSettings {
+    /// Function to check that the settings describe a simulation that can run
+    ///
+    /// # Returns
+    ///
+    /// `true` if the friction is in (0, 1], the max speed and timestep are positive and the spring
+    /// constant, number of random partners and convergence threshold are not negative
+    pub fn is_valid(&self) -> bool {
+        self.friction > 0.0
+            && self.friction <= 1.0
+            && self.max_speed > 0.0
+            && self.spring_constant >= 0.0
+            && self.random_partners >= 0
+            && self.timestep > 0.0
+            && self.convergence_threshold >= 0.0
+    }
+}
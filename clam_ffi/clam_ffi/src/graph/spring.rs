@@ -3,10 +3,9 @@ use std::collections::HashMap;
 use super::physics_node::PhysicsNode;
 
 use crate::graph;
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Spring {
     nat_len: f32,
-    k: f32,
     node1: String, //String's reference hash table
     node2: String,
     pub is_real: bool,
@@ -16,7 +15,6 @@ impl Spring {
     pub fn new(nat_len: f32, hash_code1: String, hash_code2: String, real: bool) -> Self {
         Spring {
             nat_len, //: nat_len.min(1.0),
-            k: 0.005,
             node1: hash_code1,
             node2: hash_code2,
             is_real: real,
@@ -33,6 +31,7 @@ impl Spring {
         nodes: &mut HashMap<String, PhysicsNode>,
         longest_edge: f32,
         scalar: f32,
+        k: f32,
     ) {
         //borrow ownership of nodes spring is connected to
         let node1 = nodes.get(&self.node1).unwrap();
@@ -43,7 +42,7 @@ impl Spring {
         let target_len = (self.nat_len / longest_edge.max(f32::MIN)) * scalar;
         // let target_len = (self.nat_len / longest_edge.max(f32::MIN)).max(min_length) * scalar;
         // let target_len = ((self.nat_len / longest_edge.max(f32::MIN)) * scalar).max(min_length);
-        let new_magnitude = self.k * (force_magnitude - (target_len));
+        let new_magnitude = k * (force_magnitude - (target_len));
 
         // Scale the force magnitude if the spring is not real
         // let scaled_magnitude = if !self.is_real {
@@ -63,6 +62,21 @@ impl Spring {
         node2.accelerate(new_force);
     }
 
+    /// Function to get the energy stored in the spring, which is zero at its target length
+    pub fn energy(
+        &self,
+        nodes: &HashMap<String, PhysicsNode>,
+        longest_edge: f32,
+        scalar: f32,
+        k: f32,
+    ) -> f32 {
+        let length = nodes[&self.node1]
+            .get_position()
+            .distance(nodes[&self.node2].get_position());
+        let stretch = length - (self.nat_len / longest_edge.max(f32::MIN)) * scalar;
+        0.5 * k * stretch * stretch
+    }
+
     pub fn nat_len(&self) -> f32 {
         self.nat_len
     }
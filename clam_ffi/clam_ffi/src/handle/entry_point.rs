@@ -1,5 +1,9 @@
+use abd_clam::PartitionCriteria;
+
 use crate::handle::handle::Handle;
-use crate::utils::distances::DistanceMetric;
+use crate::utils::csv_readers::CsvOptions;
+use crate::utils::data_paths::DataPaths;
+use crate::utils::distances::{DistanceMetric, MetricParams};
 use crate::utils::helpers;
 use crate::utils::types::{InHandlePtr, OutHandlePtr};
 
@@ -37,11 +41,31 @@ pub unsafe fn init_clam_struct_impl(
         }
     };
 
+    let params = match data.metric_params() {
+        Ok(params) => params,
+        Err(e) => {
+            debug!("{:?}", e);
+            return e;
+        }
+    };
+
+    let data_paths = match data.data_paths() {
+        Ok(data_paths) => data_paths,
+        Err(e) => {
+            debug!("{:?}", e);
+            return e;
+        }
+    };
+
     match Handle::new(
         &data_name,
-        data.cardinality as usize,
+        &data_paths,
+        &data.csv_options(),
         data.distance_metric,
+        &params,
         data.is_expensive,
+        &data.partition_criteria.criteria(data.cardinality as usize),
+        data.partition_criteria.seed(),
     ) {
         Ok(handle) => {
             if let Some(out_handle) = ptr {
@@ -52,7 +76,7 @@ pub unsafe fn init_clam_struct_impl(
         }
         Err(e) => {
             debug!("{:?}", e);
-            FFIError::HandleInitFailed
+            e
         }
     }
 }
@@ -72,7 +96,24 @@ pub unsafe fn init_clam_impl(
         }
     };
 
-    match Handle::new(&data_name, cardinality as usize, distance_metric, false) {
+    let data_paths = match DataPaths::resolve(&data_name, None, None, true) {
+        Ok(data_paths) => data_paths,
+        Err(e) => {
+            debug!("{:?}", e);
+            return e;
+        }
+    };
+
+    match Handle::new(
+        &data_name,
+        &data_paths,
+        &CsvOptions::default(),
+        distance_metric,
+        &MetricParams::default(),
+        false,
+        &PartitionCriteria::new(true).with_min_cardinality(cardinality as usize),
+        Some(1),
+    ) {
         Ok(handle) => {
             if let Some(out_handle) = ptr {
                 *out_handle = Box::into_raw(Box::new(handle));
@@ -83,7 +124,7 @@ pub unsafe fn init_clam_impl(
         }
         Err(e) => {
             debug!("{:?}", e);
-            FFIError::HandleInitFailed
+            e
         }
     }
 }
@@ -1,50 +1,75 @@
 extern crate nalgebra as na;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use std::path::PathBuf;
 use std::sync::Arc;
-use std::thread;
 use std::thread::JoinHandle;
 
-use abd_clam::graph::Graph;
 use abd_clam::Cluster;
-use abd_clam::Dataset;
 // use abd_clam::criteria::detect_edges;
-use abd_clam::Tree;
-use abd_clam::VecDataset;
-use abd_clam::{graph, PartitionCriteria};
+use abd_clam::PartitionCriteria;
 
 use crate::ffi_impl::cluster_ids_wrapper::ClusterIDsWrapper;
+use crate::ffi_impl::lib_impl;
+use crate::file_io::graph_export::{GraphExport, GraphFormat};
+use crate::file_io::graph_layout::GraphLayout;
+use crate::file_io::mesh_export::{MeshExportOptions, MeshFormat, MeshScene};
 // use crate::graph;
+use crate::graph::distance_cache::DistanceCacheStats;
+use crate::graph::energy::EnergyHistory;
 use crate::graph::force_directed_graph::{self, ForceDirectedGraph};
+use crate::graph::graph_builder;
+use crate::graph::graph_selection::{GraphSelection, SelectionMode};
+use crate::graph::physics_settings::PhysicsSettings;
 use crate::graph::spring;
 use crate::tree_layout::reingold_tilford;
-use crate::utils::distances::DistanceMetric;
+use crate::utils;
+use crate::utils::clam_tree::ClamTree;
+use crate::utils::coloring::{self, ClusterColoring};
+use crate::utils::csv_readers::CsvOptions;
+use crate::utils::data_paths::DataPaths;
+use crate::utils::data_types::DataType;
+use crate::utils::distances::{DistanceMetric, MetricParams};
 use crate::utils::error::FFIError;
-use crate::utils::scoring_functions::enum_to_function;
 use crate::utils::types::Graphf32;
-use crate::utils::types::Treef32;
-use crate::utils::types::{DataSetf32, Vertexf32};
-use crate::utils::{self, anomaly_readers};
+use crate::utils::types::Vertexf32;
 
 use crate::{debug, CBFnNodeVisitor, CBFnNodeVisitorMut};
 
 use crate::ffi_impl::cluster_data::ClusterData;
 use crate::ffi_impl::cluster_data_wrapper::ClusterDataWrapper;
 use crate::ffi_impl::tree_startup_data_ffi::TreeStartupDataFFI;
-use crate::graph::physics_node::PhysicsNode;
 use crate::utils::scoring_functions::ScoringFunction;
 use spring::Spring;
 
-pub struct Handle<'a> {
-    tree: Option<Treef32>,
-    clam_graph: Option<Graphf32<'a>>,
+/// The tree of a dataset and the graph, physics and layout built on it
+///
+/// The handle owns everything it holds, so it can be moved, shared between threads behind a lock
+/// and used from Rust as well as through the FFI.
+pub struct Handle {
+    tree: Option<Box<ClamTree>>,
+    graph: Option<GraphSelection>,
+    /// The clusters of `graph`, looked up in `tree` once when the graph is selected. They borrow
+    /// from the boxed tree, which does not move with the handle, and are replaced along with the
+    /// tree or the selection
+    graph_clusters: Option<Vec<&'static Vertexf32>>,
     edges: Option<Vec<Spring>>,
     current_query: Option<Vec<f32>>,
     force_directed_graph: Option<(JoinHandle<()>, Arc<ForceDirectedGraph>)>,
+    has_labels: bool,
+    seed: Option<u64>,
+    layout: Option<GraphLayout>,
+    /// The distance cache stats of the last physics run, kept once it stops
+    distance_cache_stats: Option<DistanceCacheStats>,
+    /// The energy of every frame of the last physics run, kept once it stops
+    energy_history: Option<EnergyHistory>,
+    /// The force-directed graph of the last physics run, kept once it stops so it can be restarted
+    /// from where it left off
+    stopped_physics: Option<Arc<ForceDirectedGraph>>,
+    /// Graph selections kept by name with their layouts, so the host can switch between them
+    saved_graphs: HashMap<String, (GraphSelection, Option<GraphLayout>)>,
 }
-impl<'a> Handle<'a> {
+impl Handle {
     // pub fn from(
     //     tree: Tree<Vec<f32>, f32, DataSetf32>,
     //     clam_graph: Graphf32,
@@ -75,6 +100,7 @@ impl<'a> Handle<'a> {
     ///
     /// Nothing
     pub fn shutdown(&mut self) {
+        self.graph_clusters = None;
         self.tree = None;
         // self.labels = None;
     }
@@ -88,26 +114,8 @@ impl<'a> Handle<'a> {
     /// # Returns
     ///
     /// An `Option` containing a reference to the tree or `None` if the tree does not exist
-    pub fn tree(&self) -> Option<&Treef32> {
-        self.tree.as_ref()
-    }
-
-    /// Function to get the data
-    ///
-    /// # Arguments
-    ///
-    /// * `self` - The handle
-    ///
-    /// # Returns
-    ///
-    /// An `Option` containing a reference to the data or `None` if the tree does not exist
-    pub fn data(&self) -> Option<&DataSetf32> {
-        // If the tree exists, return the data
-        return if let Some(tree) = self.tree() {
-            Some(tree.data())
-        } else {
-            None
-        };
+    pub fn tree(&self) -> Option<&ClamTree> {
+        self.tree.as_deref()
     }
 
     /// Function to get the root of the tree
@@ -135,63 +143,100 @@ impl<'a> Handle<'a> {
     ///
     /// # Returns
     ///
-    /// An `Option` containing a reference to the labels or `None` if the tree does not exist
+    /// An `Option` containing a reference to the labels or `None` if the tree does not exist or the
+    /// dataset is unlabeled
     pub fn labels(&self) -> Option<&[u8]> {
         match self.tree() {
-            Some(tree) => Some(tree.data().metadata()),
-            None => None,
+            Some(tree) if self.has_labels => Some(tree.labels()),
+            _ => None,
         }
     }
 
+    /// Function to check if the dataset of the handle has labels
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// `true` if the dataset was built with labels, `false` otherwise
+    pub fn has_labels(&self) -> bool {
+        self.has_labels
+    }
+
+    /// Function to get the seed of the handle
+    ///
+    /// The same seed gives the same tree, graph layout and physics on the same dataset
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the seed or `None` if the handle draws from the operating system
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
     /// Function to create a new handle
     ///
     /// # Arguments
     ///
     /// * `data_name` - A string slice containing the name of the data
-    /// * `cardinality` - The cardinality of the data
+    /// * `data_paths` - The resolved locations of the features and labels files
+    /// * `csv_options` - The layout of the features file if it is a `.csv` or `.tsv` file
     /// * `distance_metric` - The distance metric to use
+    /// * `params` - The parameters of the distance metric
     /// * `is_expensive` - A boolean indicating if the distance metric is expensive
+    /// * `criteria` - The criteria to partition the tree with
+    /// * `seed` - The seed for partitioning the tree and laying out the graph
     ///
     /// # Returns
     ///
     /// A `Result` containing the handle or an `FFIError` if the handle could not be created
     pub fn new(
         data_name: &str,
-        cardinality: usize,
+        data_paths: &DataPaths,
+        csv_options: &CsvOptions,
         distance_metric: DistanceMetric,
+        params: &MetricParams,
         is_expensive: bool,
+        criteria: &PartitionCriteria<f32>,
+        seed: Option<u64>,
     ) -> Result<Self, FFIError> {
-        // Get the data directory
-        let mut data_dir = std::env::current_dir().unwrap();
-        // println!(
-        //     "data dir here 123 {}",
-        //     data_dir.file_name().unwrap().to_str().unwrap()
-        // );
-        // Pop the current directory and add the data directory
-        data_dir.pop();
-        data_dir.push("data");
-        data_dir.push("anomaly_data");
-        data_dir.push("preprocessed");
-
-        // Create the partition criteria with the cardinality
-        let criteria = PartitionCriteria::new(true).with_min_cardinality(cardinality);
-
-        // Create the dataset from the data name, data directory, distance metric, and if the distance metric is expensive
-        match Self::create_dataset(data_name, &data_dir, distance_metric, is_expensive) {
-            // If the dataset was created successfully, create the tree from the dataset and partition it with the criteria
-            Ok(dataset) => {
-                let tree = Tree::new(dataset, Some(1)).partition(&criteria, None);
+        // Read the dataset in its own element type and partition a tree over it with the criteria
+        match ClamTree::build(
+            data_name,
+            data_paths,
+            csv_options,
+            distance_metric,
+            params,
+            is_expensive,
+            criteria,
+            seed,
+        ) {
+            Ok((tree, has_labels)) => {
                 // Return the handle with the tree
                 Ok(Handle {
-                    tree: Some(tree),
-                    clam_graph: None,
+                    tree: Some(Box::new(tree)),
+                    graph: None,
+                    graph_clusters: None,
                     edges: None,
                     current_query: None,
                     force_directed_graph: None,
+                    has_labels,
+                    seed,
+                    layout: None,
+                    distance_cache_stats: None,
+                    energy_history: None,
+                    stopped_physics: None,
+                    saved_graphs: HashMap::new(),
                 })
             }
-            // If the dataset could not be created, return an error
-            Err(_) => Err(FFIError::HandleInitFailed),
+            // If the dataset could not be created, pass the reason along
+            Err(e) => Err(e),
         }
     }
 
@@ -214,17 +259,43 @@ impl<'a> Handle<'a> {
             }
         };
 
-        // Get the distance metric from the data struct
-        let metric = match utils::distances::from_enum(data.distance_metric) {
-            Ok(metric) => metric,
-            Err(e) => {
-                debug!("{:?}", e);
-                return Err(e);
-            }
-        };
+        // Load the tree from the data name, element type, distance metric, and if the distance metric is expensive
+        Self::load(
+            Path::new(&data_name),
+            data.data_type,
+            data.distance_metric,
+            &data.metric_params()?,
+            data.is_expensive,
+            !data.features_only,
+            data.partition_criteria.seed(),
+        )
+    }
 
-        // Load the tree from the data name, distance metric, and if the distance metric is expensive
-        if let Ok(tree) = Treef32::load(Path::new(&data_name), metric, data.is_expensive) {
+    /// Function to load a handle from a saved tree
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path the tree was saved to
+    /// * `data_type` - The element type of the saved dataset
+    /// * `distance_metric` - The distance metric to use
+    /// * `params` - The parameters of the distance metric
+    /// * `is_expensive` - A boolean indicating if the distance metric is expensive
+    /// * `has_labels` - A boolean indicating if the saved dataset has labels
+    /// * `seed` - The seed for laying out the graph
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the handle or an `FFIError` if the handle could not be loaded
+    pub fn load(
+        path: &Path,
+        data_type: DataType,
+        distance_metric: DistanceMetric,
+        params: &MetricParams,
+        is_expensive: bool,
+        has_labels: bool,
+        seed: Option<u64>,
+    ) -> Result<Self, FFIError> {
+        if let Ok(tree) = ClamTree::load(path, data_type, distance_metric, params, is_expensive) {
             // if let Ok(tree) = Tree::<Vec<f32>, f32, DataSetf32>::load(
             //     Path::new(&data_name),
             //     metric,
@@ -232,64 +303,99 @@ impl<'a> Handle<'a> {
             // ) {
             // let tree = tree.with_ratios(false);
             Ok(Handle {
-                tree: Some(tree),
-                clam_graph: None,
+                tree: Some(Box::new(tree)),
+                graph: None,
+                graph_clusters: None,
                 edges: None,
                 current_query: None,
                 force_directed_graph: None,
+                has_labels,
+                seed,
+                layout: None,
+                distance_cache_stats: None,
+                energy_history: None,
+                stopped_physics: None,
+                saved_graphs: HashMap::new(),
             })
         } else {
             Err(FFIError::LoadTreeFailed)
         }
     }
 
-    /// Function to create a dataset
+    /// Function to select a clam graph from the tree with a CHAODA scorer, replacing the graph
+    /// selected before
+    ///
+    /// Any physics running on the previous graph is stopped once the new graph is selected
     ///
     /// # Arguments
     ///
-    /// * `data_name` - A string slice containing the name of the data
-    /// * `data_dir` - A reference to the data directory
-    /// * `distance_metric` - The distance metric to use
-    /// * `is_expensive` - A boolean indicating if the distance metric is expensive
+    /// * `self` - The handle
+    /// * `scoring_function` - The scoring function to use
+    /// * `min_depth` - The minimum depth of the graph
     ///
     /// # Returns
     ///
-    /// A `Result` containing the dataset or an `FFIError` if the dataset could not be created
-    pub fn create_dataset(
-        data_name: &str,
-        data_dir: &PathBuf,
-        distance_metric: DistanceMetric,
-        is_expensive: bool,
-    ) -> Result<DataSetf32, FFIError> {
-        // Get the distance metric from the enum
-        let metric = match utils::distances::from_enum(distance_metric) {
-            Ok(metric) => metric,
-            Err(e) => {
-                debug!("{:?}", e);
-                return Err(e);
-            }
-        };
+    /// A `Result` containing the selected graph or an `FFIError` if the graph could not be built
+    pub fn select_graph(
+        &mut self,
+        scoring_function: ScoringFunction,
+        min_depth: i32,
+    ) -> Result<&GraphSelection, FFIError> {
+        self.select_graph_with(SelectionMode::Scorer(scoring_function, min_depth))
+    }
 
-        // Read the anomaly data from the data name and data directory
-        match anomaly_readers::read_anomaly_data(data_name, data_dir, false) {
-            Ok((first_data, labels)) => {
-                // let labels = labels.iter().map(|x| *x == 1).collect::<Vec<bool>>();
-                let dataset =
-                    VecDataset::new(data_name.to_string(), first_data, metric, is_expensive)
-                        .assign_metadata(labels);
-
-                // Return the dataset if it was created successfully or an error if it was not
-                if dataset.is_ok() {
-                    Ok(dataset.unwrap())
-                } else {
-                    return Err(FFIError::HandleInitFailed);
-                }
-            }
-            Err(e) => {
-                debug!("{:?}", e);
-                Err(e)
-            }
+    /// Function to select a clam graph from the tree, replacing the graph selected before
+    ///
+    /// Any physics running on the previous graph is stopped once the new graph is selected. If the
+    /// selection fails, the previous graph, layout, and physics are left as they were
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `mode` - How to choose the clusters of the graph
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the selected graph or an `FFIError` if the graph could not be built
+    pub fn select_graph_with(&mut self, mode: SelectionMode) -> Result<&GraphSelection, FFIError> {
+        let tree = self.tree().ok_or(FFIError::GraphBuildFailed)?;
+        let selection = GraphSelection::select_with(tree, mode)?;
+        let clusters = self.resolve_clusters(&selection)?;
+
+        if self.force_directed_graph.is_some() {
+            unsafe { self.force_physics_shutdown() };
         }
+        self.layout = None;
+        self.stopped_physics = None;
+        self.graph_clusters = Some(clusters);
+        Ok(self.graph.insert(selection))
+    }
+
+    /// Function to look up the clusters of a selection in the tree, to keep with the selection
+    ///
+    /// The clusters are only handed out borrowed from the handle. The tree is boxed, so they stay
+    /// where they are while the handle holds the tree, and `graph_clusters` is cleared before the
+    /// tree is dropped
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `graph` - The selection to look up
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the clusters in selection order or `FFIError::GraphBuildFailed` if the
+    /// tree does not exist or does not hold one of them
+    fn resolve_clusters(
+        &self,
+        graph: &GraphSelection,
+    ) -> Result<Vec<&'static Vertexf32>, FFIError> {
+        let tree = self.tree().ok_or(FFIError::GraphBuildFailed)?;
+        let clusters = graph.clusters(tree)?;
+        Ok(clusters
+            .into_iter()
+            .map(|cluster| unsafe { &*(cluster as *const Vertexf32) })
+            .collect())
     }
 
     /// Function to create a clam graph from the tree
@@ -305,33 +411,166 @@ impl<'a> Handle<'a> {
     ///
     /// An `FFIError` indicating if the graph was created successfully or not
     pub fn init_clam_graph(
-        &'a mut self,
+        &mut self,
         scoring_function: ScoringFunction,
         min_depth: i32,
         cluster_selector: CBFnNodeVisitor,
     ) -> FFIError {
-        // If the tree exists
-        if let Some(tree) = &self.tree {
-            // Get the scoring function from the enum
-            match enum_to_function(&scoring_function) {
-                Ok(scorer) => {
-                    // Create the graph from the tree and the scoring function
-                    if let Ok(graph) = Graph::from_tree(tree, &scorer, min_depth as usize) {
-                        self.clam_graph = Some(graph);
-                        for cluster in self.clam_graph().unwrap().ordered_clusters() {
-                            let baton = ClusterDataWrapper::from_cluster(cluster);
-                            cluster_selector(Some(baton.data()));
-                        }
+        self.init_clam_graph_with(
+            SelectionMode::Scorer(scoring_function, min_depth),
+            cluster_selector,
+        )
+    }
 
-                        return FFIError::Ok;
-                    }
-                }
-                Err(e) => {
-                    return e;
-                }
+    /// Function to create a clam graph from the tree with any selection mode
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `mode` - How to choose the clusters of the graph
+    /// * `cluster_selector` - The cluster selector function
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` indicating if the graph was created successfully or not
+    pub fn init_clam_graph_with(
+        &mut self,
+        mode: SelectionMode,
+        cluster_selector: CBFnNodeVisitor,
+    ) -> FFIError {
+        if let Err(e) = self.select_graph_with(mode) {
+            return e;
+        }
+
+        self.visit_graph_clusters(cluster_selector)
+    }
+
+    /// Function to keep the selected graph and its layout under a name
+    ///
+    /// A selection already kept under the name is replaced
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `name` - The name to keep the selection under
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` indicating if the selection was kept or `FFIError::GraphBuildFailed` if no
+    /// graph is selected
+    pub fn save_graph_selection(&mut self, name: &str) -> FFIError {
+        let graph = match &self.graph {
+            Some(graph) => graph.clone(),
+            None => return FFIError::GraphBuildFailed,
+        };
+        let layout = match &self.force_directed_graph {
+            Some(force_directed_graph) => Some(GraphLayout::capture(
+                &force_directed_graph.1,
+                graph.mode().clone(),
+            )),
+            None => self.layout.clone(),
+        };
+
+        self.saved_graphs.insert(name.to_string(), (graph, layout));
+        FFIError::Ok
+    }
+
+    /// Function to make a graph kept with `save_graph_selection` the selected graph again
+    ///
+    /// Any running physics is stopped, and every cluster of the graph is passed to the cluster
+    /// selector, at its position in the kept layout if there is one
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `name` - The name the selection was kept under
+    /// * `cluster_selector` - The cluster selector function
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` indicating if the selection was switched to or
+    /// `FFIError::GraphSelectionNotFound` if nothing is kept under the name
+    pub fn switch_graph_selection(
+        &mut self,
+        name: &str,
+        cluster_selector: CBFnNodeVisitor,
+    ) -> FFIError {
+        let (graph, layout) = match self.saved_graphs.get(name) {
+            Some(saved) => saved.clone(),
+            None => return FFIError::GraphSelectionNotFound,
+        };
+        let clusters = match self.resolve_clusters(&graph) {
+            Ok(clusters) => clusters,
+            Err(e) => return e,
+        };
+        if self.force_directed_graph.is_some() {
+            unsafe { self.force_physics_shutdown() };
+        }
+
+        self.graph = Some(graph);
+        self.graph_clusters = Some(clusters);
+        self.layout = layout;
+        self.stopped_physics = None;
+        self.visit_graph_clusters(cluster_selector)
+    }
+
+    /// Function to forget a graph kept with `save_graph_selection`
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `name` - The name the selection was kept under
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` indicating if the selection was removed or `FFIError::GraphSelectionNotFound`
+    /// if nothing is kept under the name
+    pub fn remove_graph_selection(&mut self, name: &str) -> FFIError {
+        match self.saved_graphs.remove(name) {
+            Some(_) => FFIError::Ok,
+            None => FFIError::GraphSelectionNotFound,
+        }
+    }
+
+    /// Function to get the names of the kept graph selections in sorted order
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    pub fn graph_selection_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.saved_graphs.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    /// Function to pass every cluster of the selected graph to the cluster selector
+    ///
+    /// Clusters in the current layout are passed at their position in it
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `cluster_selector` - The cluster selector function
+    fn visit_graph_clusters(&self, cluster_selector: CBFnNodeVisitor) -> FFIError {
+        let clusters = match self.graph_clusters() {
+            Ok(clusters) => clusters,
+            Err(e) => return e,
+        };
+        let positions: HashMap<&str, glam::Vec3> = self
+            .layout
+            .iter()
+            .flat_map(|layout| layout.positions.iter())
+            .map(|(id, position)| (id.as_str(), *position))
+            .collect();
+
+        for &cluster in clusters {
+            let mut baton = ClusterDataWrapper::from_cluster(cluster);
+            if let Some(position) = positions.get(cluster.name().as_str()) {
+                baton.data_mut().pos = *position;
             }
+            cluster_selector(Some(baton.data()));
         }
-        FFIError::GraphBuildFailed
+        FFIError::Ok
     }
 
     /// Function to create a clam graph from the tree without a visual
@@ -346,28 +585,14 @@ impl<'a> Handle<'a> {
     ///
     /// An `FFIError` indicating if the graph was created successfully or not
     pub fn init_clam_graph_no_visual(
-        &'a mut self,
+        &mut self,
         scoring_function: ScoringFunction,
         min_depth: i32,
     ) -> FFIError {
-        // If the tree exists
-        if let Some(tree) = &self.tree {
-            // Get the scoring function from the enum
-            match enum_to_function(&scoring_function) {
-                Ok(scorer) => {
-                    // Create the graph from the tree and the scoring function
-                    if let Ok(graph) = Graph::from_tree(tree, &scorer, min_depth as usize) {
-                        self.clam_graph = Some(graph);
-
-                        return FFIError::Ok;
-                    }
-                }
-                Err(e) => {
-                    return e;
-                }
-            }
+        match self.select_graph(scoring_function, min_depth) {
+            Ok(_) => FFIError::Ok,
+            Err(e) => e,
         }
-        FFIError::GraphBuildFailed
     }
 
     /// Function to force a shutdown of the graph physics
@@ -383,6 +608,7 @@ impl<'a> Handle<'a> {
         // If the force directed graph exists, shutdown the physics
         if let Some(force_directed_graph) = &self.force_directed_graph {
             force_directed_graph::force_shutdown(&force_directed_graph.1);
+            self.capture_layout();
             let _ = self.force_directed_graph.take().unwrap().0.join();
 
             self.force_directed_graph = None;
@@ -404,7 +630,9 @@ impl<'a> Handle<'a> {
     /// An `FFIError` indicating if the edges were initialized successfully or not
     pub unsafe fn init_unity_edges(&mut self, edge_detect_cb: CBFnNodeVisitorMut) -> FFIError {
         if let Some(force_directed_graph) = &self.force_directed_graph {
-            force_directed_graph::init_unity_edges(&force_directed_graph.1, edge_detect_cb);
+            force_directed_graph::init_unity_edges(force_directed_graph.1.edges(), edge_detect_cb);
+        } else if let Some(layout) = &self.layout {
+            force_directed_graph::init_unity_edges(&layout.springs, edge_detect_cb);
         }
         FFIError::PhysicsAlreadyShutdown
     }
@@ -420,28 +648,620 @@ impl<'a> Handle<'a> {
     ///
     /// An `FFIError` indicating if the physics was updated successfully or not
     pub unsafe fn physics_update_async(&mut self, updater: CBFnNodeVisitor) -> FFIError {
-        // If the force directed graph exists, update the physics
+        self.physics_update_with(|force_directed_graph, clusters, tree| {
+            force_directed_graph::try_update_unity(force_directed_graph, clusters, tree, updater)
+        })
+    }
+
+    /// Function to update the physics asynchronously and copy the new positions to a buffer
+    ///
+    /// Unlike `physics_update_async`, no callback is made and no string is allocated per node.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `out_positions` - Three floats for every node, in the order of `physics_node_order`
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` indicating if the physics was updated successfully or not. The buffer is only
+    /// written when it is `PhysicsRunning`
+    pub fn physics_update_positions(&mut self, out_positions: &mut [f32]) -> FFIError {
+        self.physics_update_with(|force_directed_graph, clusters, tree| {
+            force_directed_graph::try_update_positions(
+                force_directed_graph,
+                clusters,
+                tree,
+                out_positions,
+            )
+        })
+    }
+
+    /// Function to hand the next frame of the running physics to `update`, or stop the physics if
+    /// its thread has finished
+    ///
+    /// Physics only runs on the selected graph, so every frame is handed the clusters looked up
+    /// when the graph was selected rather than looking them up in the tree again
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `update` - Applies the frame to the graph and reports the positions to the host
+    fn physics_update_with(
+        &mut self,
+        update: impl FnOnce(&ForceDirectedGraph, &[&Vertexf32], &ClamTree) -> FFIError,
+    ) -> FFIError {
+        // If the physics is finished, join the thread and set the force directed graph to `None`
+        if self.join_finished_physics() {
+            return FFIError::PhysicsFinished;
+        }
+
+        // If the force directed graph exists, update the physics with the clusters kept for it
         if let Some(force_directed_graph) = &self.force_directed_graph {
-            // If the physics is finished, join the thread and set the force directed graph to `None`
-            let is_finished = force_directed_graph.0.is_finished();
+            return match self.graph_clusters() {
+                Ok(clusters) => update(&force_directed_graph.1, clusters, self.tree().unwrap()),
+                Err(e) => e,
+            };
+        }
 
-            return if is_finished {
+        FFIError::PhysicsAlreadyShutdown
+    }
+
+    /// Function to join the physics thread if it has finished, keeping its layout as a shutdown
+    /// does
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// `true` if a finished physics thread was joined
+    fn join_finished_physics(&mut self) -> bool {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) if force_directed_graph.0.is_finished() => {
+                self.capture_layout();
                 let _ = self.force_directed_graph.take().unwrap().0.join();
-                self.force_directed_graph = None;
                 debug!("shutting down physics");
+                true
+            }
+            _ => false,
+        }
+    }
 
-                FFIError::PhysicsFinished
-            } else {
-                force_directed_graph::try_update_unity(
-                    &force_directed_graph.1,
-                    self.clam_graph().as_ref().unwrap(),
-                    self.tree().as_ref().unwrap(),
-                    updater,
-                )
-            };
+    /// Function to keep the positions, distance cache stats and energy history of the running
+    /// physics so they can be read once it stops, and the graph itself so it can be restarted
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    fn capture_layout(&mut self) {
+        if let (Some(force_directed_graph), Some(graph)) = (&self.force_directed_graph, &self.graph)
+        {
+            self.layout = Some(GraphLayout::capture(
+                &force_directed_graph.1,
+                graph.mode().clone(),
+            ));
         }
+        if let Some(force_directed_graph) = &self.force_directed_graph {
+            self.distance_cache_stats = Some(force_directed_graph.1.distance_cache_stats());
+            self.energy_history = Some(force_directed_graph.1.energy_history());
+            self.stopped_physics = Some(force_directed_graph.1.clone());
+        }
+    }
 
-        FFIError::PhysicsAlreadyShutdown
+    /// Function to get the running physics, or else the physics that last stopped
+    fn current_physics(&self) -> Option<&Arc<ForceDirectedGraph>> {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => Some(&force_directed_graph.1),
+            None => self.stopped_physics.as_ref(),
+        }
+    }
+
+    /// Function to get the names of the nodes of the physics in the order their positions are
+    /// copied by `copy_physics_positions` and `physics_update_positions`
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// The names of the nodes of the running physics, or else of the physics that last stopped, or
+    /// `None` if it has not run on the selected graph
+    pub fn physics_node_order(&self) -> Option<&[String]> {
+        self.current_physics()
+            .map(|force_directed_graph| force_directed_graph.node_order())
+    }
+
+    /// Function to copy the position of every node of the physics to a buffer without waiting for
+    /// the physics thread
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `out_positions` - Three floats for every node, in the order of `physics_node_order`
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` that is `Ok` if the positions were copied, `BufferTooSmall` if the buffer has
+    /// no room for every node, `PhysicsNotReady` if the physics thread is computing a frame or
+    /// `PhysicsAlreadyShutdown` if the physics has not run on the selected graph
+    pub fn copy_physics_positions(&self, out_positions: &mut [f32]) -> FFIError {
+        match self.current_physics() {
+            Some(force_directed_graph) => force_directed_graph.copy_positions(out_positions),
+            None => FFIError::PhysicsAlreadyShutdown,
+        }
+    }
+
+    /// Function to stop moving the nodes of the running physics until it is resumed
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` that is `Ok` if the physics was paused or `PhysicsAlreadyShutdown` if it is
+    /// not running
+    pub fn pause_physics(&self) -> FFIError {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => {
+                force_directed_graph.1.pause();
+                FFIError::Ok
+            }
+            None => FFIError::PhysicsAlreadyShutdown,
+        }
+    }
+
+    /// Function to continue moving the nodes of the running physics after it was paused or stepped
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` that is `Ok` if the physics was resumed or `PhysicsAlreadyShutdown` if it is
+    /// not running
+    pub fn resume_physics(&self) -> FFIError {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => {
+                force_directed_graph.1.resume();
+                FFIError::Ok
+            }
+            None => FFIError::PhysicsAlreadyShutdown,
+        }
+    }
+
+    /// Function to pause the running physics and then let it run a number of frames
+    ///
+    /// Each call to `physics_update_async` runs one of the frames, and returns
+    /// `FFIError::PhysicsPaused` once they have all run.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `frames` - The number of frames to run
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` that is `Ok` if the frames were queued or `PhysicsAlreadyShutdown` if the
+    /// physics is not running
+    pub fn step_physics(&self, frames: u32) -> FFIError {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => {
+                force_directed_graph.1.step(frames);
+                FFIError::Ok
+            }
+            None => FFIError::PhysicsAlreadyShutdown,
+        }
+    }
+
+    /// Function to hold the node of a cluster at a position while the rest of the running physics
+    /// moves around it
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `id` - The name of the cluster
+    /// * `position` - Where to hold the node
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` that is `Ok` if the node was pinned, `NodeNotInGraph` if the cluster is not in
+    /// the graph or `PhysicsAlreadyShutdown` if the physics is not running
+    pub fn pin_node(&self, id: &str, position: glam::Vec3) -> FFIError {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => match force_directed_graph.1.pin_node(id, position) {
+                Ok(()) => FFIError::Ok,
+                Err(e) => e,
+            },
+            None => FFIError::PhysicsAlreadyShutdown,
+        }
+    }
+
+    /// Function to drag a node held with `pin_node` to another position
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `id` - The name of the cluster
+    /// * `position` - Where to hold the node now
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` that is `Ok` if the node was moved, `NodeNotPinned` if it is not pinned,
+    /// `NodeNotInGraph` if the cluster is not in the graph or `PhysicsAlreadyShutdown` if the
+    /// physics is not running
+    pub fn move_pinned_node(&self, id: &str, position: glam::Vec3) -> FFIError {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => {
+                match force_directed_graph.1.move_pinned_node(id, position) {
+                    Ok(()) => FFIError::Ok,
+                    Err(e) => e,
+                }
+            }
+            None => FFIError::PhysicsAlreadyShutdown,
+        }
+    }
+
+    /// Function to let the running physics move a node held with `pin_node` again
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `id` - The name of the cluster
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` that is `Ok` if the node was unpinned, `NodeNotInGraph` if the cluster is not
+    /// in the graph or `PhysicsAlreadyShutdown` if the physics is not running
+    pub fn unpin_node(&self, id: &str) -> FFIError {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => match force_directed_graph.1.unpin_node(id) {
+                Ok(()) => FFIError::Ok,
+                Err(e) => e,
+            },
+            None => FFIError::PhysicsAlreadyShutdown,
+        }
+    }
+
+    /// Function to run the physics again from the current positions of its nodes
+    ///
+    /// Running physics is stopped first. The nodes keep their positions and velocities, and the
+    /// energy history carries on from the last frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `iterations` - The most frames to run. Zero or less runs as many as the first run could
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` that is `PhysicsRunning` if the physics was restarted or `NoPhysicsToRestart`
+    /// if it has not run on the selected graph
+    pub fn restart_physics(&mut self, iterations: i32) -> FFIError {
+        if self.force_directed_graph.is_some() {
+            unsafe { self.force_physics_shutdown() };
+        }
+        let force_directed_graph = match self.stopped_physics.take() {
+            Some(force_directed_graph) => force_directed_graph,
+            None => return FFIError::NoPhysicsToRestart,
+        };
+
+        let iterations = if iterations > 0 {
+            iterations
+        } else {
+            force_directed_graph.max_iters()
+        };
+        force_directed_graph.restart();
+        let worker = graph_builder::spawn_physics_thread(force_directed_graph.clone(), iterations);
+        self.force_directed_graph = Some((worker, force_directed_graph));
+        debug!("restarting physics for {} iterations", iterations);
+        FFIError::PhysicsRunning
+    }
+
+    /// Function to get the settings of the physics
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// The settings of the running physics, or the default settings if it is not running
+    pub fn physics_settings(&self) -> PhysicsSettings {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => force_directed_graph.1.settings(),
+            None => PhysicsSettings::default(),
+        }
+    }
+
+    /// Function to change the settings of the running physics
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `settings` - The settings the next frame runs with
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` that is `Ok` if the settings were changed, `InvalidPhysicsSettings` if they
+    /// cannot run or `PhysicsAlreadyShutdown` if the physics is not running
+    pub fn set_physics_settings(&self, settings: PhysicsSettings) -> FFIError {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => match force_directed_graph.1.set_settings(settings) {
+                Ok(()) => FFIError::Ok,
+                Err(e) => e,
+            },
+            None => FFIError::PhysicsAlreadyShutdown,
+        }
+    }
+
+    /// Function to get the energy of every frame of the physics
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// The history of the running physics, or else of the last time it ran, or `None` if it has
+    /// not run
+    pub fn energy_history(&self) -> Option<EnergyHistory> {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => Some(force_directed_graph.1.energy_history()),
+            None => self.energy_history.clone(),
+        }
+    }
+
+    /// Function to get how well the physics has been caching the distances between clusters
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// The stats of the running physics, or else of the last time it ran, or `None` if it has not
+    /// run
+    pub fn distance_cache_stats(&self) -> Option<DistanceCacheStats> {
+        match &self.force_directed_graph {
+            Some(force_directed_graph) => Some(force_directed_graph.1.distance_cache_stats()),
+            None => self.distance_cache_stats,
+        }
+    }
+
+    /// Function to save the force-directed layout of the graph
+    ///
+    /// The layout is taken from the physics if it is running, or else from the last time it ran or
+    /// was loaded
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `path` - The path to save the layout to
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` indicating if the layout was saved successfully or not
+    pub fn save_graph_layout(&self, path: &Path) -> FFIError {
+        let result = match (&self.force_directed_graph, &self.graph, &self.layout) {
+            (Some(force_directed_graph), Some(graph), _) => {
+                GraphLayout::capture(&force_directed_graph.1, graph.mode().clone()).save(path)
+            }
+            (None, _, Some(layout)) => layout.save(path),
+            _ => return FFIError::NoGraphLayout,
+        };
+
+        match result {
+            Ok(()) => FFIError::Ok,
+            Err(e) => e,
+        }
+    }
+
+    /// Function to export the selected graph to GraphML, GEXF or DOT
+    ///
+    /// The positions and virtual springs of the force-directed layout are included if the physics
+    /// is running or has run on the graph
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `path` - The path to export the graph to
+    /// * `format` - The file format to write
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` indicating if the graph was exported successfully or not
+    pub fn export_graph(&self, path: &Path, format: GraphFormat) -> FFIError {
+        let (tree, clam_graph) = match (self.tree(), self.clam_graph()) {
+            (Some(tree), Some(clam_graph)) => (tree, clam_graph),
+            _ => return FFIError::GraphBuildFailed,
+        };
+        let layout = self.graph_layout();
+        match GraphExport::new(tree, &clam_graph, self.labels(), layout.as_ref()).save(path, format)
+        {
+            Ok(()) => FFIError::Ok,
+            Err(e) => e,
+        }
+    }
+
+    /// Function to export a layout of the tree or the selected graph as a glTF, OBJ or PLY mesh
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `path` - The path to export the mesh to
+    /// * `format` - The file format to write
+    /// * `options` - The layout, coloring and sizing of the clusters
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` indicating if the mesh was exported successfully or not
+    pub fn export_mesh(
+        &self,
+        path: &Path,
+        format: MeshFormat,
+        options: &MeshExportOptions,
+    ) -> FFIError {
+        match MeshScene::new(self, options).and_then(|scene| scene.save(path, format)) {
+            Ok(()) => FFIError::Ok,
+            Err(e) => e,
+        }
+    }
+
+    /// Function to get the force-directed layout of the selected graph
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// The current positions if the physics is running, or else the layout from the last time it
+    /// ran or was loaded, or `None` if there is no layout
+    pub fn graph_layout(&self) -> Option<GraphLayout> {
+        match (&self.force_directed_graph, &self.graph) {
+            (Some(force_directed_graph), Some(graph)) => Some(GraphLayout::capture(
+                &force_directed_graph.1,
+                graph.mode().clone(),
+            )),
+            _ => self.layout.clone(),
+        }
+    }
+
+    /// Function to color clusters the way one of the coloring functions would
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `clusters` - The clusters to color
+    /// * `coloring` - The coloring to use
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the color of every cluster, `FFIError::NoLabels` if the coloring needs
+    /// labels and the dataset is unlabeled, `FFIError::QueryIsNull` if it needs a query and none is
+    /// set, or another `FFIError` if the clusters could not be colored
+    pub fn cluster_colors(
+        &self,
+        clusters: &[&Vertexf32],
+        coloring: ClusterColoring,
+    ) -> Result<Vec<glam::Vec3>, FFIError> {
+        let labels: &[u8] = match coloring {
+            ClusterColoring::DominantLabel | ClusterColoring::Entropy => {
+                self.labels().ok_or(FFIError::NoLabels)?
+            }
+            _ => &[],
+        };
+
+        match coloring {
+            ClusterColoring::Uniform => Ok(vec![coloring::UNIFORM_COLOR; clusters.len()]),
+            ClusterColoring::DominantLabel => {
+                let num_unique_labels = labels.iter().collect::<HashSet<_>>().len();
+                let colors = utils::helpers::label_colors();
+                if num_unique_labels > colors.len() {
+                    return Err(FFIError::TooManyLabels);
+                }
+                clusters
+                    .iter()
+                    .map(|c| {
+                        lib_impl::calc_cluster_dominant_color(c, labels, num_unique_labels, &colors)
+                            .map_err(|_| FFIError::ColoringFailed)
+                    })
+                    .collect()
+            }
+            ClusterColoring::Entropy => {
+                // The entropy colors show the share of outliers, so only binary labels are colored
+                if labels.iter().any(|&label| label > 1) {
+                    return Err(FFIError::ColoringFailed);
+                }
+                Ok(clusters
+                    .iter()
+                    .map(|c| lib_impl::calc_cluster_entropy_color(c, labels))
+                    .collect())
+            }
+            ClusterColoring::DistanceToQuery => {
+                let query = self.current_query.as_ref().ok_or(FFIError::QueryIsNull)?;
+                let tree = self.tree().ok_or(FFIError::HandleInitFailed)?;
+                let distances = clusters
+                    .iter()
+                    .map(|c| tree.distance_to_query(c, query))
+                    .collect::<Option<Vec<f32>>>()
+                    .ok_or(FFIError::UnsupportedDataType)?;
+                let max_distance = distances.iter().cloned().fold(0.0, f32::max);
+                Ok(distances
+                    .iter()
+                    .map(|&d| {
+                        if max_distance > 0.0 {
+                            coloring::distance_color(d / max_distance)
+                        } else {
+                            coloring::distance_color(0.0)
+                        }
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Function to restore a saved force-directed layout without running the physics
+    ///
+    /// The clam graph is rebuilt with the selection mode stored in the layout, and
+    /// every cluster in it is passed to the cluster selector at its saved position
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    /// * `path` - The path the layout was saved to
+    /// * `cluster_selector` - The cluster selector function
+    ///
+    /// # Returns
+    ///
+    /// An `FFIError` indicating if the layout was restored successfully or not, which is
+    /// `PhysicsRunning` while the physics thread is still running
+    pub fn load_graph_layout(
+        &mut self,
+        path: &Path,
+        cluster_selector: CBFnNodeVisitor,
+    ) -> FFIError {
+        self.join_finished_physics();
+        if self.force_directed_graph.is_some() {
+            return FFIError::PhysicsRunning;
+        }
+
+        let layout = match GraphLayout::load(path) {
+            Ok(layout) => layout,
+            Err(e) => return e,
+        };
+
+        // Select the same clusters the layout was run on
+        if let Some(tree) = self.tree() {
+            let graph = match GraphSelection::select_with(tree, layout.selection.clone()) {
+                Ok(graph) => graph,
+                Err(e) => return e,
+            };
+
+            let clusters = match self.resolve_clusters(&graph) {
+                Ok(clusters) => clusters,
+                Err(e) => return e,
+            };
+            let names: HashSet<String> = clusters.iter().map(|c| c.name()).collect();
+            if names.len() != layout.positions.len()
+                || layout.positions.iter().any(|(id, _)| !names.contains(id))
+            {
+                debug!("layout does not match the clusters of the graph");
+                return FFIError::LayoutMismatch;
+            }
+
+            self.graph = Some(graph);
+            self.graph_clusters = Some(clusters);
+            self.layout = Some(layout);
+            self.stopped_physics = None;
+            return self.visit_graph_clusters(cluster_selector);
+        }
+        FFIError::HandleInitFailed
     }
 
     /// Function to set the graph
@@ -452,6 +1272,7 @@ impl<'a> Handle<'a> {
     /// * `graph` - The graph to set
     pub fn set_graph(&mut self, graph: (JoinHandle<()>, Arc<ForceDirectedGraph>)) {
         self.force_directed_graph = Some(graph);
+        self.stopped_physics = None;
     }
 
     /// Function to get the number of edges in the graph
@@ -464,8 +1285,8 @@ impl<'a> Handle<'a> {
     ///
     /// An `i32` containing the number of edges in the graph or -1 if the graph does not exist
     pub fn get_num_edges_in_graph(&self) -> i32 {
-        if let Some(g) = self.clam_graph() {
-            return g.edge_cardinality() as i32;
+        if let Some(graph) = &self.graph {
+            return graph.edge_cardinality() as i32;
         }
         return -1;
     }
@@ -491,7 +1312,10 @@ impl<'a> Handle<'a> {
                         let mut baton_data = ClusterDataWrapper::from_cluster(cluster);
 
                         baton_data.data_mut().dist_to_query =
-                            cluster.distance_to_instance(self.data().unwrap(), query);
+                            match self.tree().unwrap().distance_to_query(cluster, query) {
+                                Some(distance) => distance,
+                                None => return FFIError::UnsupportedDataType,
+                            };
 
                         node_visitor(Some(baton_data.data()));
                     } else {
@@ -726,15 +1550,50 @@ impl<'a> Handle<'a> {
 
     /// Function to get the clam graph of the handle
     ///
+    /// The graph is built from the selection and its clusters each time, so callers that need it
+    /// more than once should keep the returned graph
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// An `Option` containing the graph or `None` if no graph was selected
+    pub fn clam_graph(&self) -> Option<Graphf32<'_>> {
+        match (&self.graph, self.graph_clusters()) {
+            (Some(graph), Ok(clusters)) => graph.graph(clusters).ok(),
+            _ => None,
+        }
+    }
+
+    /// Function to get the graph selection of the handle
+    ///
     /// # Arguments
     ///
     /// * `self` - The handle
     ///
     /// # Returns
     ///
-    /// An `Option` containing a reference to the graph or `None` if the graph does not exist
-    pub fn clam_graph(&self) -> Option<&Graph<'a, f32>> {
-        self.clam_graph.as_ref()
+    /// An `Option` containing a reference to the selection or `None` if no graph was selected
+    pub fn graph_selection(&self) -> Option<&GraphSelection> {
+        self.graph.as_ref()
+    }
+
+    /// Function to get the clusters of the selected graph
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The handle
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the clusters in selection order or `FFIError::GraphBuildFailed` if no
+    /// graph was selected
+    pub fn graph_clusters(&self) -> Result<&[&Vertexf32], FFIError> {
+        self.graph_clusters
+            .as_deref()
+            .ok_or(FFIError::GraphBuildFailed)
     }
 
     /// Function to get the tree height of the handle
@@ -765,10 +1624,7 @@ impl<'a> Handle<'a> {
     ///
     /// A `Result` containing a reference to the cluster or an `FFIError` if the cluster could not be found
     // why isnt string taken by reference?
-    pub unsafe fn get_cluster_from_string(
-        &self,
-        cluster_id: String,
-    ) -> Result<&Vertexf32, FFIError> {
+    pub fn get_cluster_from_string(&self, cluster_id: String) -> Result<&Vertexf32, FFIError> {
         let mut parts = cluster_id.split('-');
 
         // If the cluster ID has an offset and a cardinality, get the cluster
@@ -795,11 +1651,7 @@ impl<'a> Handle<'a> {
     /// # Returns
     ///
     /// A `Result` containing a reference to the cluster or an `FFIError` if the cluster could not be found
-    pub unsafe fn get_cluster(
-        &self,
-        offset: usize,
-        cardinality: usize,
-    ) -> Result<&Vertexf32, FFIError> {
+    pub fn get_cluster(&self, offset: usize, cardinality: usize) -> Result<&Vertexf32, FFIError> {
         // If the tree exists, get the cluster from the offset and cardinality
         if let Some(tree) = self.tree() {
             return if let Some(cluster) = tree.get_cluster(offset, cardinality) {
@@ -866,3 +1718,14 @@ impl<'a> Handle<'a> {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Handle>();
+    }
+}
@@ -1,24 +1,31 @@
 use std::ffi::c_char;
-mod accuracy_benchmarks;
-mod ffi_impl;
-mod file_io;
+pub mod accuracy_benchmarks;
+pub mod ffi_impl;
+pub mod file_io;
 pub mod graph;
 pub mod handle;
 mod tests;
 mod tree_layout;
-mod utils;
+pub mod utils;
 
 use crate::ffi_impl::lib_impl::{
     free_resource, max_lfd_impl, max_vertex_degree_impl, vertex_degree_impl,
 };
 use crate::ffi_impl::tree_startup_data_ffi::TreeStartupDataFFI;
-use crate::file_io::load_save::save_cakes_single_impl;
+use crate::file_io::load_save::{
+    export_graph_impl, export_mesh_impl, load_graph_layout_impl, render_tree_impl,
+    save_cakes_single_impl, save_graph_layout_impl,
+};
+use crate::file_io::graph_export::GraphFormat;
+use crate::file_io::mesh_export::{MeshExportOptions, MeshFormat};
+use crate::file_io::tree_render::{ImageFormat, TreeRenderOptions};
 use ffi_impl::{
     cluster_data::ClusterData, cluster_ids::ClusterIDs, lib_impl::*, string_ffi::StringFFI,
 };
 use graph::entry::*;
 use tree_layout::entry_point::{draw_hierarchy_impl, draw_hierarchy_offset_from_impl};
 use utils::{
+    custom_metrics::CBFnDistance,
     debug,
     distances::DistanceMetric,
     error::FFIError,
@@ -28,6 +35,10 @@ use utils::{
 use crate::handle::entry_point::{
     init_clam_impl, init_clam_struct_impl, load_cakes_struct_impl, shutdown_clam_impl,
 };
+use crate::graph::distance_cache::DistanceCacheStats;
+use crate::graph::energy::FrameEnergy;
+use crate::graph::physics_settings::PhysicsSettings;
+use crate::graph::graph_selection::SelectionMode;
 use crate::utils::scoring_functions::ScoringFunction;
 
 type CBFnNodeVisitor = extern "C" fn(Option<&ClusterData>) -> ();
@@ -181,6 +192,72 @@ pub unsafe extern "C" fn shutdown_clam(context_ptr: OutHandlePtr) -> FFIError {
     shutdown_clam_impl(context_ptr)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn save_graph_layout(
+    ptr: InHandlePtr,
+    file_name: *const u8,
+    name_len: i32,
+) -> FFIError {
+    save_graph_layout_impl(ptr, file_name, name_len)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn load_graph_layout(
+    ptr: InHandlePtr,
+    file_name: *const u8,
+    name_len: i32,
+    cluster_selector: CBFnNodeVisitor,
+) -> FFIError {
+    load_graph_layout_impl(ptr, file_name, name_len, cluster_selector)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn export_graph(
+    ptr: InHandlePtr,
+    file_name: *const u8,
+    name_len: i32,
+    format: GraphFormat,
+) -> FFIError {
+    export_graph_impl(ptr, file_name, name_len, format)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn export_mesh(
+    ptr: InHandlePtr,
+    file_name: *const u8,
+    name_len: i32,
+    format: MeshFormat,
+    options: Option<&MeshExportOptions>,
+) -> FFIError {
+    export_mesh_impl(ptr, file_name, name_len, format, options)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn render_tree(
+    ptr: InHandlePtr,
+    file_name: *const u8,
+    name_len: i32,
+    format: ImageFormat,
+    options: Option<&TreeRenderOptions>,
+) -> FFIError {
+    render_tree_impl(ptr, file_name, name_len, format, options)
+}
+
+// ------------------------------------- Custom Metrics -------------------------------------
+#[no_mangle]
+pub unsafe extern "C" fn register_distance_function(
+    name: *const c_char,
+    callback: Option<CBFnDistance>,
+    is_expensive: bool,
+) -> FFIError {
+    register_distance_function_impl(name, callback, is_expensive)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn unregister_distance_function(name: *const c_char) -> FFIError {
+    unregister_distance_function_impl(name)
+}
+
 // ------------------------------------- Graph Clam Init -------------------------------------
 #[no_mangle]
 pub extern "C" fn init_clam_graph(
@@ -195,6 +272,78 @@ pub extern "C" fn init_clam_graph(
     FFIError::HandleInitFailed
 }
 
+#[no_mangle]
+pub extern "C" fn init_clam_graph_at_depth(
+    context: InHandlePtr,
+    depth: i32,
+    cluster_selector: CBFnNodeVisitor,
+) -> FFIError {
+    if let Some(handle) = context {
+        let mode = SelectionMode::Depth(depth.max(0) as usize);
+        return handle.init_clam_graph_with(mode, cluster_selector);
+    }
+    FFIError::HandleInitFailed
+}
+
+#[no_mangle]
+pub extern "C" fn init_clam_graph_leaf_cut(
+    context: InHandlePtr,
+    target_count: i32,
+    cluster_selector: CBFnNodeVisitor,
+) -> FFIError {
+    if let Some(handle) = context {
+        let mode = SelectionMode::LeafCut(target_count.max(1) as usize);
+        return handle.init_clam_graph_with(mode, cluster_selector);
+    }
+    FFIError::HandleInitFailed
+}
+
+#[no_mangle]
+pub extern "C" fn init_clam_graph_by_threshold(
+    context: InHandlePtr,
+    max_radius: f32,
+    max_lfd: f32,
+    cluster_selector: CBFnNodeVisitor,
+) -> FFIError {
+    if let Some(handle) = context {
+        let mode = SelectionMode::Threshold {
+            max_radius,
+            max_lfd,
+        };
+        return handle.init_clam_graph_with(mode, cluster_selector);
+    }
+    FFIError::HandleInitFailed
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn init_clam_graph_from_ids(
+    context: InHandlePtr,
+    ids: *const *const c_char,
+    count: i32,
+    cluster_selector: CBFnNodeVisitor,
+) -> FFIError {
+    init_clam_graph_from_ids_impl(context, ids, count, cluster_selector)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn save_clam_graph(context: InHandlePtr, name: *const c_char) -> FFIError {
+    save_clam_graph_impl(context, name)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn switch_clam_graph(
+    context: InHandlePtr,
+    name: *const c_char,
+    cluster_selector: CBFnNodeVisitor,
+) -> FFIError {
+    switch_clam_graph_impl(context, name, cluster_selector)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn remove_clam_graph(context: InHandlePtr, name: *const c_char) -> FFIError {
+    remove_clam_graph_impl(context, name)
+}
+
 // -------------------------------------  Tree helpers -------------------------------------
 
 #[no_mangle]
@@ -240,6 +389,15 @@ pub unsafe extern "C" fn get_cluster_label(ptr: InHandlePtr, cluster_id: *const
     get_cluster_label_impl(ptr, cluster_id)
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn has_labels(ptr: InHandlePtr) -> bool {
+    if let Some(handle) = ptr {
+        return handle.has_labels();
+    }
+    debug!("handle not created");
+    false
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn max_vertex_degree(ptr: InHandlePtr) -> i32 {
     max_vertex_degree_impl(ptr)
@@ -303,7 +461,107 @@ pub extern "C" fn init_force_directed_graph(
     scalar: f32,
     max_iters: i32,
 ) -> FFIError {
-    init_force_directed_graph_impl(context, scalar, max_iters)
+    init_force_directed_graph_impl(context, scalar, max_iters, None)
+}
+
+#[no_mangle]
+pub extern "C" fn init_force_directed_graph_with_settings(
+    context: InHandlePtr,
+    scalar: f32,
+    max_iters: i32,
+    settings: Option<&PhysicsSettings>,
+) -> FFIError {
+    init_force_directed_graph_impl(context, scalar, max_iters, settings)
+}
+
+#[no_mangle]
+pub extern "C" fn set_physics_settings(
+    context: InHandlePtr,
+    settings: Option<&PhysicsSettings>,
+) -> FFIError {
+    set_physics_settings_impl(context, settings)
+}
+
+#[no_mangle]
+pub extern "C" fn get_physics_settings(
+    context: InHandlePtr,
+    out_settings: Option<&mut PhysicsSettings>,
+) -> FFIError {
+    get_physics_settings_impl(context, out_settings)
+}
+
+#[no_mangle]
+pub extern "C" fn pause_physics(context: InHandlePtr) -> FFIError {
+    pause_physics_impl(context)
+}
+
+#[no_mangle]
+pub extern "C" fn resume_physics(context: InHandlePtr) -> FFIError {
+    resume_physics_impl(context)
+}
+
+#[no_mangle]
+pub extern "C" fn step_physics(context: InHandlePtr, frames: i32) -> FFIError {
+    step_physics_impl(context, frames)
+}
+
+#[no_mangle]
+pub extern "C" fn restart_physics(context: InHandlePtr, max_iters: i32) -> FFIError {
+    restart_physics_impl(context, max_iters)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn physics_update_positions(
+    context: InHandlePtr,
+    out_positions: *mut f32,
+    len: i32,
+) -> FFIError {
+    physics_update_positions_impl(context, out_positions, len)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn copy_physics_positions(
+    context: InHandlePtr,
+    out_positions: *mut f32,
+    len: i32,
+) -> FFIError {
+    copy_physics_positions_impl(context, out_positions, len)
+}
+
+#[no_mangle]
+pub extern "C" fn get_physics_node_count(context: InHandlePtr) -> i32 {
+    get_physics_node_count_impl(context)
+}
+
+#[no_mangle]
+pub extern "C" fn visit_physics_node_order(
+    context: InHandlePtr,
+    visitor: CBFnNodeVisitor,
+) -> FFIError {
+    visit_physics_node_order_impl(context, visitor)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn pin_node(
+    context: InHandlePtr,
+    cluster_id: *const c_char,
+    position: glam::Vec3,
+) -> FFIError {
+    pin_node_impl(context, cluster_id, position)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn move_pinned_node(
+    context: InHandlePtr,
+    cluster_id: *const c_char,
+    position: glam::Vec3,
+) -> FFIError {
+    move_pinned_node_impl(context, cluster_id, position)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn unpin_node(context: InHandlePtr, cluster_id: *const c_char) -> FFIError {
+    unpin_node_impl(context, cluster_id)
 }
 
 #[no_mangle]
@@ -351,6 +609,33 @@ pub extern "C" fn get_num_graph_components(ptr: InHandlePtr) -> i32 {
     get_num_graph_components_impl(ptr)
 }
 
+#[no_mangle]
+pub extern "C" fn get_energy_history_len(ptr: InHandlePtr) -> i32 {
+    get_energy_history_len_impl(ptr)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn get_energy_history(
+    ptr: InHandlePtr,
+    out_energy: *mut FrameEnergy,
+    len: i32,
+) -> FFIError {
+    get_energy_history_impl(ptr, out_energy, len)
+}
+
+#[no_mangle]
+pub extern "C" fn get_converged_iteration(ptr: InHandlePtr) -> i32 {
+    get_converged_iteration_impl(ptr)
+}
+
+#[no_mangle]
+pub extern "C" fn get_distance_cache_stats(
+    ptr: InHandlePtr,
+    out_stats: Option<&mut DistanceCacheStats>,
+) -> FFIError {
+    get_distance_cache_stats_impl(ptr, out_stats)
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn force_physics_shutdown(ptr: InHandlePtr) -> i32 {
     if let Some(handle) = ptr {
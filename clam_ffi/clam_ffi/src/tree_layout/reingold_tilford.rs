@@ -26,6 +26,37 @@ pub fn run_offset(
     update_unity_positions_offset(layout_root, start_pos, node_visitor, max_depth)
 }
 
+/// The positions of the clusters in a Reingold-Tilford layout of a tree
+pub struct TreePositions {
+    /// The name and position of every cluster in the layout
+    pub positions: Vec<(String, Vec3)>,
+    /// The names of every parent and child in the layout
+    pub edges: Vec<(String, String)>,
+}
+
+/// Function to lay out the tree down to a depth and collect the positions of its clusters
+///
+/// The root is at the origin and deeper clusters are further down the y axis, as they are drawn
+pub fn positions(clam_root: &Vertexf32, max_depth: i32) -> TreePositions {
+    let mut positions = Vec::new();
+    let mut edges = Vec::new();
+    let mut stack: Vec<_> = reingold_impl::Node::create_layout(clam_root, max_depth)
+        .into_iter()
+        .collect();
+    while let Some(node) = stack.pop() {
+        let node = node.as_ref().borrow();
+        positions.push((node.get_name(), Vec3::new(node.get_x(), -node.get_y(), 0.0)));
+        for child in [node.get_left_child(), node.get_right_child()]
+            .into_iter()
+            .flatten()
+        {
+            edges.push((node.get_name(), child.as_ref().borrow().get_name()));
+            stack.push(child);
+        }
+    }
+    TreePositions { positions, edges }
+}
+
 fn update_unity_positions_offset(
     root: reingold_impl::Link,
     start_pos: &Vec3,
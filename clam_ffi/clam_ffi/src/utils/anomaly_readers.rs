@@ -1,10 +1,14 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::{debug, utils::error::FFIError};
+use crate::{
+    debug,
+    utils::{data_paths::DataPaths, error::FFIError},
+};
 use ndarray::prelude::*;
+use ndarray_npy::ReadableElement;
 
 pub static ANOMALY_DATASETS: &[&str] = &[
     "annthyroid",  // 0
@@ -38,57 +42,62 @@ pub fn read_anomaly_data(
     data_dir: &PathBuf,
     normalized: bool,
 ) -> Result<(Vec<Vec<f32>>, Vec<u8>), FFIError> {
-    // let mut data_dir = std::env::current_dir().unwrap();
-    // data_dir.pop();
-    // data_dir.push("data");
-    // data_dir.push("anomaly_data");
-    // data_dir.push("preprocessed");
     if !data_dir.exists() {
-        let p = data_dir.to_str().unwrap_or("path is empty");
+        debug!("data directory not found: {}", data_dir.display());
         return Err(FFIError::PathNotFound);
     }
-    debug!("herer");
-    let features = {
-        let mut path = data_dir.clone();
-        path.push(if normalized {
+
+    let paths = DataPaths {
+        features: data_dir.join(if normalized {
             format!("{name}_features_normalized.npy")
         } else {
             format!("{name}_features.npy")
-        });
-
-        if !path.exists() {
-            // debug!("path {:?} - {}", path, path.exists());
-            let p = path.to_str().unwrap_or("path is empty");
-            return Err(FFIError::PathNotFound);
-        }
-
-        let features: Array2<f32> = ndarray_npy::read_npy(&path).map_err(|error| {
-            debug!("{:?}", error);
-            FFIError::PathNotFound
-        })?;
-
-        features.outer_iter().map(|row| row.to_vec()).collect()
+        }),
+        labels: Some(data_dir.join(format!("{name}_scores.npy"))),
     };
 
-    debug!("herer2");
+    let (features, scores) = read_npy_data(&paths)?;
+    Ok((features, scores.ok_or(FFIError::LabelsFileNotFound)?))
+}
+
+pub fn read_npy_data(paths: &DataPaths) -> Result<(Vec<Vec<f32>>, Option<Vec<u8>>), FFIError> {
+    Ok((read_npy_features(&paths.features)?, read_npy_labels(paths)?))
+}
 
-    let scores = {
-        let mut path = data_dir.clone();
-        path.push(format!("{name}_scores.npy"));
+/// Function to read a two dimensional `.npy` file as one instance per row
+///
+/// The element type `T` must match the dtype of the file
+pub fn read_npy_features<T: ReadableElement + Clone>(path: &Path) -> Result<Vec<Vec<T>>, FFIError> {
+    if !path.exists() {
+        debug!("features file not found: {}", path.display());
+        return Err(FFIError::FeaturesFileNotFound);
+    }
 
-        if !path.exists() {
-            return Err(FFIError::PathNotFound);
-        }
+    let features: Array2<T> = ndarray_npy::read_npy(path).map_err(|error| {
+        debug!("{:?}", error);
+        FFIError::PathNotFound
+    })?;
 
-        let features: Array1<u8> = ndarray_npy::read_npy(&path).map_err(|error| {
-            debug!("{:?}", error);
-            FFIError::PathNotFound
-        })?;
+    Ok(features.outer_iter().map(|row| row.to_vec()).collect())
+}
 
-        debug!("herer3");
+/// Function to read the labels file of a dataset, if it has one
+pub fn read_npy_labels(paths: &DataPaths) -> Result<Option<Vec<u8>>, FFIError> {
+    // Datasets without a labels file are read as unlabeled
+    match &paths.labels {
+        Some(labels) => {
+            if !labels.exists() {
+                debug!("labels file not found: {}", labels.display());
+                return Err(FFIError::LabelsFileNotFound);
+            }
 
-        features.to_vec()
-    };
+            let scores: Array1<u8> = ndarray_npy::read_npy(labels).map_err(|error| {
+                debug!("{:?}", error);
+                FFIError::PathNotFound
+            })?;
 
-    Ok((features, scores))
+            Ok(Some(scores.to_vec()))
+        }
+        None => Ok(None),
+    }
 }
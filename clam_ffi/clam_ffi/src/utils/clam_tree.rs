@@ -0,0 +1,373 @@
+use std::path::Path;
+
+use abd_clam::graph::{Graph, MetaMLScorer};
+use abd_clam::{Cluster, Dataset, Instance, PartitionCriteria, Tree, VecDataset};
+
+use crate::debug;
+use crate::utils::anomaly_readers;
+use crate::utils::csv_readers::{self, CsvOptions};
+use crate::utils::data_paths::DataPaths;
+use crate::utils::data_types::{self, DataType};
+use crate::utils::distances::{self, DistanceMetric, MetricBinding, MetricParams};
+use crate::utils::error::FFIError;
+use crate::utils::sequence_readers;
+use crate::utils::types::{
+    Graphf32, TreeBits, TreeString, Treef32, Treef64, Treei32, Treeu8, Vertexf32,
+};
+
+/// A tree over a dataset of any of the supported element types, with the parameters its distance
+/// function reads
+pub struct ClamTree {
+    tree: TypedTree,
+    /// Dropped after the tree, so the slots its metric reads stay bound while the tree is alive
+    _binding: MetricBinding,
+}
+
+/// A tree over a dataset of one of the supported element types
+///
+/// Every variant measures distances as `f32`, so the clusters and graphs built from them have the
+/// same types whatever the data is.
+pub enum TypedTree {
+    F32(Treef32),
+    F64(Treef64),
+    U8(Treeu8),
+    I32(Treei32),
+    Bits(TreeBits),
+    String(TreeString),
+}
+
+/// Runs the same expression against the tree inside any variant
+macro_rules! dispatch {
+    ($tree:expr, $inner:ident => $body:expr) => {
+        match &$tree.tree {
+            TypedTree::F32($inner) => $body,
+            TypedTree::F64($inner) => $body,
+            TypedTree::U8($inner) => $body,
+            TypedTree::I32($inner) => $body,
+            TypedTree::Bits($inner) => $body,
+            TypedTree::String($inner) => $body,
+        }
+    };
+}
+
+impl ClamTree {
+    /// Function to read a dataset and build a partitioned tree from it
+    ///
+    /// The element type follows the dtype of a `.npy` features file. Delimited text files are
+    /// always read as `f32` and FASTA or plain text files as `String`.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_name` - The name of the dataset
+    /// * `data_paths` - The resolved locations of the features and labels files
+    /// * `csv_options` - The layout of the features file if it is a `.csv` or `.tsv` file
+    /// * `distance_metric` - The distance metric to use
+    /// * `params` - The parameters of the distance metric, which the tree keeps while it is alive
+    /// * `is_expensive` - A boolean indicating if the distance metric is expensive
+    /// * `criteria` - The criteria to partition the tree with
+    /// * `seed` - The seed for choosing cluster centers and poles
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the tree and whether the dataset has labels, or an `FFIError`
+    pub fn build(
+        data_name: &str,
+        data_paths: &DataPaths,
+        csv_options: &CsvOptions,
+        distance_metric: DistanceMetric,
+        params: &MetricParams,
+        is_expensive: bool,
+        criteria: &PartitionCriteria<f32>,
+        seed: Option<u64>,
+    ) -> Result<(Self, bool), FFIError> {
+        let binding = distances::bind(distance_metric, params)?;
+        let is_expensive = distances::is_expensive(&binding, is_expensive);
+        if csv_readers::is_delimited_file(&data_paths.features) {
+            let (features, labels) = csv_readers::read_csv_data(&data_paths.features, csv_options)?;
+            let metric = distances::from_enum(distance_metric, &binding)?;
+            let has_labels = labels.is_some();
+            let tree = partition(
+                data_name,
+                features,
+                labels,
+                metric,
+                is_expensive,
+                criteria,
+                seed,
+            )?;
+            return Ok((ClamTree::new(TypedTree::F32(tree), binding), has_labels));
+        }
+
+        let labels = anomaly_readers::read_npy_labels(data_paths)?;
+        let has_labels = labels.is_some();
+        let features_path = data_paths.features.as_path();
+
+        if sequence_readers::is_sequence_file(features_path) {
+            let tree = partition(
+                data_name,
+                sequence_readers::read_sequences(features_path)?,
+                labels,
+                distances::string_from_enum(distance_metric)?,
+                is_expensive,
+                criteria,
+                seed,
+            )?;
+            return Ok((ClamTree::new(TypedTree::String(tree), binding), has_labels));
+        }
+
+        let tree = match data_types::npy_data_type(features_path)? {
+            DataType::F32 => TypedTree::F32(partition(
+                data_name,
+                anomaly_readers::read_npy_features(features_path)?,
+                labels,
+                distances::from_enum(distance_metric, &binding)?,
+                is_expensive,
+                criteria,
+                seed,
+            )?),
+            DataType::F64 => TypedTree::F64(partition(
+                data_name,
+                anomaly_readers::read_npy_features(features_path)?,
+                labels,
+                distances::numeric_from_enum(distance_metric, &binding)?,
+                is_expensive,
+                criteria,
+                seed,
+            )?),
+            DataType::U8 => TypedTree::U8(partition(
+                data_name,
+                anomaly_readers::read_npy_features(features_path)?,
+                labels,
+                distances::numeric_from_enum(distance_metric, &binding)?,
+                is_expensive,
+                criteria,
+                seed,
+            )?),
+            DataType::I32 => TypedTree::I32(partition(
+                data_name,
+                anomaly_readers::read_npy_features(features_path)?,
+                labels,
+                distances::numeric_from_enum(distance_metric, &binding)?,
+                is_expensive,
+                criteria,
+                seed,
+            )?),
+            DataType::Bits => {
+                let features = anomaly_readers::read_npy_features::<bool>(features_path)?
+                    .iter()
+                    .map(|row| data_types::pack_bits(row))
+                    .collect();
+                TypedTree::Bits(partition(
+                    data_name,
+                    features,
+                    labels,
+                    distances::bits_from_enum(distance_metric, &binding)?,
+                    is_expensive,
+                    criteria,
+                    seed,
+                )?)
+            }
+            DataType::String => return Err(FFIError::UnsupportedDataType),
+        };
+
+        Ok((ClamTree::new(tree, binding), has_labels))
+    }
+
+    /// Function to load a saved tree
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path the tree was saved to
+    /// * `data_type` - The element type of the saved dataset
+    /// * `distance_metric` - The distance metric to use
+    /// * `params` - The parameters of the distance metric, which the tree keeps while it is alive
+    /// * `is_expensive` - A boolean indicating if the distance metric is expensive
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the tree or an `FFIError` if the tree could not be loaded
+    pub fn load(
+        path: &Path,
+        data_type: DataType,
+        distance_metric: DistanceMetric,
+        params: &MetricParams,
+        is_expensive: bool,
+    ) -> Result<Self, FFIError> {
+        let binding = distances::bind(distance_metric, params)?;
+        let is_expensive = distances::is_expensive(&binding, is_expensive);
+        let tree = match data_type {
+            DataType::F32 => Treef32::load(
+                path,
+                distances::from_enum(distance_metric, &binding)?,
+                is_expensive,
+            )
+            .map(TypedTree::F32),
+            DataType::F64 => Treef64::load(
+                path,
+                distances::numeric_from_enum(distance_metric, &binding)?,
+                is_expensive,
+            )
+            .map(TypedTree::F64),
+            DataType::U8 => Treeu8::load(
+                path,
+                distances::numeric_from_enum(distance_metric, &binding)?,
+                is_expensive,
+            )
+            .map(TypedTree::U8),
+            DataType::I32 => Treei32::load(
+                path,
+                distances::numeric_from_enum(distance_metric, &binding)?,
+                is_expensive,
+            )
+            .map(TypedTree::I32),
+            DataType::Bits => TreeBits::load(
+                path,
+                distances::bits_from_enum(distance_metric, &binding)?,
+                is_expensive,
+            )
+            .map(TypedTree::Bits),
+            DataType::String => TreeString::load(
+                path,
+                distances::string_from_enum(distance_metric)?,
+                is_expensive,
+            )
+            .map(TypedTree::String),
+        };
+
+        match tree {
+            Ok(tree) => Ok(ClamTree::new(tree, binding)),
+            Err(e) => {
+                debug!("{:?}", e);
+                Err(FFIError::LoadTreeFailed)
+            }
+        }
+    }
+
+    fn new(tree: TypedTree, binding: MetricBinding) -> Self {
+        ClamTree {
+            tree,
+            _binding: binding,
+        }
+    }
+
+    /// Function to get the tree if its dataset has `f32` elements
+    pub fn as_f32(&self) -> Option<&Treef32> {
+        match &self.tree {
+            TypedTree::F32(tree) => Some(tree),
+            _ => None,
+        }
+    }
+
+    /// Function to save the tree so it can be loaded with `ClamTree::load`
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        dispatch!(self, tree => tree.save(path))
+    }
+
+    /// Function to get the element type of the dataset
+    pub fn data_type(&self) -> DataType {
+        match &self.tree {
+            TypedTree::F32(_) => DataType::F32,
+            TypedTree::F64(_) => DataType::F64,
+            TypedTree::U8(_) => DataType::U8,
+            TypedTree::I32(_) => DataType::I32,
+            TypedTree::Bits(_) => DataType::Bits,
+            TypedTree::String(_) => DataType::String,
+        }
+    }
+
+    /// Function to get the name of the dataset
+    pub fn name(&self) -> &str {
+        dispatch!(self, tree => tree.data().name())
+    }
+
+    /// Function to get the number of instances in the dataset
+    pub fn cardinality(&self) -> usize {
+        dispatch!(self, tree => tree.cardinality())
+    }
+
+    /// Function to get the depth of the deepest leaf
+    pub fn depth(&self) -> usize {
+        dispatch!(self, tree => tree.depth())
+    }
+
+    /// Function to get the root cluster
+    pub fn root(&self) -> &Vertexf32 {
+        dispatch!(self, tree => tree.root())
+    }
+
+    /// Function to get a cluster by its offset and cardinality
+    pub fn get_cluster(&self, offset: usize, cardinality: usize) -> Option<&Vertexf32> {
+        dispatch!(self, tree => tree.get_cluster(offset, cardinality))
+    }
+
+    /// Function to map an index in the permuted order of the tree back to the original dataset
+    pub fn original_index(&self, index: usize) -> usize {
+        dispatch!(self, tree => tree.data().original_index(index))
+    }
+
+    /// Function to get the labels of the instances, in the permuted order of the tree
+    pub fn labels(&self) -> &[u8] {
+        dispatch!(self, tree => tree.data().metadata())
+    }
+
+    /// Function to get the distance between the centers of two clusters
+    pub fn cluster_distance(&self, left: &Vertexf32, right: &Vertexf32) -> f32 {
+        dispatch!(self, tree => left.distance_to_other(tree.data(), right))
+    }
+
+    /// Function to get the distance from the center of a cluster to a query
+    ///
+    /// The query is converted to the element type of the dataset. For bit-vectors any non-zero
+    /// value is a set bit. String datasets cannot take a numeric query and give `None`.
+    pub fn distance_to_query(&self, cluster: &Vertexf32, query: &[f32]) -> Option<f32> {
+        let distance = match &self.tree {
+            TypedTree::F32(tree) => cluster.distance_to_instance(tree.data(), &query.to_vec()),
+            TypedTree::F64(tree) => cluster.distance_to_instance(tree.data(), &convert(query)),
+            TypedTree::U8(tree) => cluster.distance_to_instance(tree.data(), &convert(query)),
+            TypedTree::I32(tree) => cluster.distance_to_instance(tree.data(), &convert(query)),
+            TypedTree::Bits(tree) => {
+                let bits: Vec<bool> = query.iter().map(|&value| value != 0.0).collect();
+                cluster.distance_to_instance(tree.data(), &data_types::pack_bits(&bits))
+            }
+            TypedTree::String(_) => return None,
+        };
+
+        Some(distance)
+    }
+
+    /// Function to build a clam graph from the tree
+    pub fn build_graph(
+        &self,
+        scorer: &MetaMLScorer,
+        min_depth: usize,
+    ) -> Result<Graphf32<'_>, String> {
+        dispatch!(self, tree => Graph::from_tree(tree, scorer, min_depth))
+    }
+}
+
+/// Function to build a dataset from instances and optional labels and partition a tree over it
+///
+/// Unlabeled datasets get a label of zero for every instance
+fn partition<I: Instance>(
+    data_name: &str,
+    features: Vec<I>,
+    labels: Option<Vec<u8>>,
+    metric: fn(&I, &I) -> f32,
+    is_expensive: bool,
+    criteria: &PartitionCriteria<f32>,
+    seed: Option<u64>,
+) -> Result<Tree<I, f32, VecDataset<I, f32, u8>, Vertexf32>, FFIError> {
+    let labels = labels.unwrap_or_else(|| vec![0; features.len()]);
+    let dataset = VecDataset::new(data_name.to_string(), features, metric, is_expensive)
+        .assign_metadata(labels)
+        .map_err(|e| {
+            debug!("{:?}", e);
+            FFIError::HandleInitFailed
+        })?;
+
+    Ok(Tree::new(dataset, seed).partition(criteria, seed))
+}
+
+fn convert<T: ::distances::Number>(query: &[f32]) -> Vec<T> {
+    query.iter().map(|&value| T::from(value)).collect()
+}
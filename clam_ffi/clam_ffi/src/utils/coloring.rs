@@ -0,0 +1,39 @@
+/// The ways clusters can be colored, matching the coloring functions of the FFI
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ClusterColoring {
+    /// Every cluster has the same color
+    Uniform,
+    /// The color of the most common label in the cluster, as in `color_clusters_by_dominant_label`
+    DominantLabel,
+    /// The share of outliers and inliers in the cluster, as in `color_clusters_by_entropy`
+    Entropy,
+    /// The distance from the cluster to the current query, from red when close to blue when far
+    DistanceToQuery,
+}
+
+impl ClusterColoring {
+    /// Function to get a coloring from its name, such as `dominant_label`
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "uniform" => Ok(ClusterColoring::Uniform),
+            "dominant_label" => Ok(ClusterColoring::DominantLabel),
+            "entropy" => Ok(ClusterColoring::Entropy),
+            "distance_to_query" => Ok(ClusterColoring::DistanceToQuery),
+            _ => Err(format!("unknown coloring {}", name)),
+        }
+    }
+}
+
+/// The color of clusters that are colored uniformly
+pub const UNIFORM_COLOR: glam::Vec3 = glam::Vec3::new(0.8, 0.8, 0.8);
+
+/// Function to get the color of a distance to the query
+///
+/// # Arguments
+///
+/// * `distance` - The distance, scaled to between 0 and 1
+pub fn distance_color(distance: f32) -> glam::Vec3 {
+    let distance = distance.clamp(0.0, 1.0);
+    glam::Vec3::new(1.0 - distance, 0.0, distance)
+}
@@ -0,0 +1,254 @@
+use std::{collections::BTreeSet, path::Path};
+
+use crate::{debug, utils::error::FFIError};
+
+/// Options describing how a delimited text dataset is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// The field delimiter. `None` picks a tab for `.tsv` files and a comma otherwise
+    pub delimiter: Option<u8>,
+    /// Whether the first row is a header that should be skipped
+    pub has_header: bool,
+    /// The column holding the class labels, or `None` if the file has no labels. Negative values
+    /// count from the end, so `-1` is the last column
+    pub label_column: Option<i32>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: None,
+            has_header: true,
+            label_column: Some(-1),
+        }
+    }
+}
+
+/// Function to check if a path points to a delimited text file
+pub fn is_delimited_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("csv") | Some("tsv")
+    )
+}
+
+/// Function to read a delimited text file with one row per instance and an optional label column
+///
+/// # Arguments
+///
+/// * `path` - The path to the `.csv` or `.tsv` file
+/// * `options` - The layout of the file
+///
+/// # Returns
+///
+/// A `Result` containing the features and the labels encoded as `u8`, if the file has a label
+/// column, or an `FFIError`
+pub fn read_csv_data(
+    path: &Path,
+    options: &CsvOptions,
+) -> Result<(Vec<Vec<f32>>, Option<Vec<u8>>), FFIError> {
+    if !path.is_file() {
+        debug!("features file not found: {}", path.display());
+        return Err(FFIError::FeaturesFileNotFound);
+    }
+
+    let delimiter = options.delimiter.unwrap_or_else(|| {
+        if path.extension().and_then(|ext| ext.to_str()) == Some("tsv") {
+            b'\t'
+        } else {
+            b','
+        }
+    });
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(options.has_header)
+        .from_path(path)
+        .map_err(|error| {
+            debug!("{:?}", error);
+            FFIError::PathNotFound
+        })?;
+
+    let mut features = Vec::new();
+    let mut label_names = Vec::new();
+
+    for (row, record) in reader.records().enumerate() {
+        let record = record.map_err(|error| {
+            debug!("{:?}", error);
+            FFIError::DataParseFailed
+        })?;
+
+        let label_column = match options.label_column {
+            Some(label_column) => {
+                Some(resolve_column(label_column, record.len()).ok_or_else(|| {
+                    debug!(
+                        "label column {} out of range for row {} with {} columns",
+                        label_column,
+                        row,
+                        record.len()
+                    );
+                    FFIError::DataParseFailed
+                })?)
+            }
+            None => None,
+        };
+
+        let mut instance = Vec::with_capacity(record.len());
+        for (column, field) in record.iter().enumerate() {
+            if Some(column) == label_column {
+                label_names.push(field.trim().to_string());
+            } else {
+                let value = field.trim().parse::<f32>().map_err(|error| {
+                    debug!("row {} column {}: {:?}", row, column, error);
+                    FFIError::DataParseFailed
+                })?;
+                instance.push(value);
+            }
+        }
+        features.push(instance);
+    }
+
+    let labels = match options.label_column {
+        Some(_) => Some(encode_labels(&label_names)?),
+        None => None,
+    };
+
+    Ok((features, labels))
+}
+
+/// Function to map class names to the `u8` labels used by the coloring code
+///
+/// If every name is already an integer in `0..=255` the integers are kept as they are, so files
+/// with numeric labels keep their meaning. Otherwise the unique names are sorted and numbered in
+/// that order.
+///
+/// # Arguments
+///
+/// * `names` - The class name of each instance
+///
+/// # Returns
+///
+/// A `Result` containing the labels or `FFIError::TooManyLabels` if there are more than 256 classes
+pub fn encode_labels(names: &[String]) -> Result<Vec<u8>, FFIError> {
+    let numeric: Result<Vec<u8>, _> = names.iter().map(|name| name.parse::<u8>()).collect();
+    if let Ok(labels) = numeric {
+        return Ok(labels);
+    }
+
+    let classes: Vec<&String> = names.iter().collect::<BTreeSet<_>>().into_iter().collect();
+    if classes.len() > u8::MAX as usize + 1 {
+        return Err(FFIError::TooManyLabels);
+    }
+
+    Ok(names
+        .iter()
+        .map(|name| {
+            classes
+                .binary_search(&name)
+                .unwrap_or_else(|_| unreachable!("every name is one of the classes"))
+                as u8
+        })
+        .collect())
+}
+
+fn resolve_column(column: i32, num_columns: usize) -> Option<usize> {
+    let column = if column < 0 {
+        num_columns as i64 + column as i64
+    } else {
+        column as i64
+    };
+
+    if column >= 0 && (column as usize) < num_columns {
+        Some(column as usize)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::PathBuf};
+
+    fn write_temp_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_encode_labels() {
+        let numeric = vec!["0".to_string(), "1".to_string(), "0".to_string()];
+        assert_eq!(encode_labels(&numeric).unwrap(), vec![0, 1, 0]);
+
+        let names = vec![
+            "setosa".to_string(),
+            "virginica".to_string(),
+            "setosa".to_string(),
+            "versicolor".to_string(),
+        ];
+        assert_eq!(encode_labels(&names).unwrap(), vec![0, 2, 0, 1]);
+
+        let too_many: Vec<String> = (0..300).map(|i| format!("class_{i}")).collect();
+        assert_eq!(encode_labels(&too_many), Err(FFIError::TooManyLabels));
+    }
+
+    #[test]
+    fn test_read_csv_with_header() {
+        let path = write_temp_file(
+            "clam_ffi_test_read_csv_with_header.csv",
+            "a,b,class\n1.0,2.0,cat\n3.5,4.0,dog\n",
+        );
+
+        let (features, labels) = read_csv_data(&path, &CsvOptions::default()).unwrap();
+        assert_eq!(features, vec![vec![1.0, 2.0], vec![3.5, 4.0]]);
+        assert_eq!(labels, Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn test_read_tsv_first_column_labels() {
+        let path = write_temp_file(
+            "clam_ffi_test_read_tsv_first_column_labels.tsv",
+            "1\t0.5\t0.25\n0\t1.5\t1.25\n",
+        );
+        let options = CsvOptions {
+            delimiter: None,
+            has_header: false,
+            label_column: Some(0),
+        };
+
+        let (features, labels) = read_csv_data(&path, &options).unwrap();
+        assert_eq!(features, vec![vec![0.5, 0.25], vec![1.5, 1.25]]);
+        assert_eq!(labels, Some(vec![1, 0]));
+    }
+
+    #[test]
+    fn test_read_csv_without_labels() {
+        let path = write_temp_file(
+            "clam_ffi_test_read_csv_without_labels.csv",
+            "1.0,2.0,3.0\n4.0,5.0,6.0\n",
+        );
+        let options = CsvOptions {
+            delimiter: None,
+            has_header: false,
+            label_column: None,
+        };
+
+        let (features, labels) = read_csv_data(&path, &options).unwrap();
+        assert_eq!(features, vec![vec![1.0, 2.0, 3.0], vec![4.0, 5.0, 6.0]]);
+        assert_eq!(labels, None);
+    }
+
+    #[test]
+    fn test_read_csv_bad_value() {
+        let path = write_temp_file(
+            "clam_ffi_test_read_csv_bad_value.csv",
+            "a,class\nnot_a_number,cat\n",
+        );
+
+        assert_eq!(
+            read_csv_data(&path, &CsvOptions::default()),
+            Err(FFIError::DataParseFailed)
+        );
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+
+use crate::debug;
+use crate::utils::error::FFIError;
+
+/// A distance function supplied by the host. It takes pointers to two vectors of the given length
+pub type CBFnDistance = extern "C" fn(*const f32, *const f32, i32) -> f32;
+
+/// The most distinct distance functions the trees alive at once can use
+pub const CUSTOM_SLOTS: usize = 8;
+
+#[derive(Copy, Clone)]
+struct CustomMetric {
+    callback: CBFnDistance,
+    is_expensive: bool,
+}
+
+/// The distance functions registered by name, and the name registered last
+#[derive(Default)]
+struct Registry {
+    metrics: HashMap<String, CustomMetric>,
+    latest: Option<String>,
+}
+
+/// A registered distance function held by the trees built with it
+struct Slot {
+    name: String,
+    metric: CustomMetric,
+    users: usize,
+}
+
+static REGISTRY: Mutex<Option<Registry>> = Mutex::new(None);
+
+/// The distance function each copy of `distance` calls. Datasets need plain function pointers, so
+/// each tree reads the function it was built with from its own slot, which is emptied once every
+/// tree holding it is dropped
+static SLOTS: RwLock<[Option<Slot>; CUSTOM_SLOTS]> = RwLock::new([const { None }; CUSTOM_SLOTS]);
+
+/// Function to check if a tree holds the distance function registered under a name
+fn is_bound(name: &str) -> bool {
+    SLOTS
+        .read()
+        .unwrap()
+        .iter()
+        .any(|slot| matches!(slot, Some(slot) if slot.name == name))
+}
+
+/// Function to register a distance function under a name
+///
+/// The most recently registered function is the one used by `DistanceMetric::Custom` when no name
+/// is given. Registering a name again replaces its function unless a tree holds it.
+///
+/// # Arguments
+///
+/// * `name` - The name to register the function under
+/// * `callback` - The distance function
+/// * `is_expensive` - A boolean indicating if the distance function is expensive
+///
+/// # Returns
+///
+/// `FFIError::CustomMetricInUse` if a tree holds the function already registered under the name
+pub fn register(name: &str, callback: CBFnDistance, is_expensive: bool) -> Result<(), FFIError> {
+    let mut registry = REGISTRY.lock().unwrap();
+    if is_bound(name) {
+        debug!("distance function {} is used by a tree", name);
+        return Err(FFIError::CustomMetricInUse);
+    }
+
+    let registry = registry.get_or_insert_with(Registry::default);
+    let metric = CustomMetric {
+        callback,
+        is_expensive,
+    };
+    registry.metrics.insert(name.to_string(), metric);
+    registry.latest = Some(name.to_string());
+    Ok(())
+}
+
+/// Function to remove a registered distance function
+///
+/// # Returns
+///
+/// `FFIError::CustomMetricNotFound` if no function is registered under the name or
+/// `FFIError::CustomMetricInUse` if a tree holds it
+pub fn unregister(name: &str) -> Result<(), FFIError> {
+    let mut registry = REGISTRY.lock().unwrap();
+    let registry = registry.get_or_insert_with(Registry::default);
+    if !registry.metrics.contains_key(name) {
+        debug!("no distance function registered as {}", name);
+        return Err(FFIError::CustomMetricNotFound);
+    }
+    if is_bound(name) {
+        debug!("distance function {} is used by a tree", name);
+        return Err(FFIError::CustomMetricInUse);
+    }
+
+    registry.metrics.remove(name);
+    if registry.latest.as_deref() == Some(name) {
+        registry.latest = None;
+    }
+    Ok(())
+}
+
+/// Function to hold the slot of a registered distance function for a tree, taking a free one if
+/// no tree holds the function
+///
+/// # Arguments
+///
+/// * `name` - The name of the function, or `None` for the most recently registered one
+///
+/// # Returns
+///
+/// The index of the slot, `FFIError::CustomMetricNotFound` if no function is registered under the
+/// name or `FFIError::TooManyCustomMetrics` if every slot holds another function
+pub fn bind(name: Option<&str>) -> Result<usize, FFIError> {
+    let registry = REGISTRY.lock().unwrap();
+    let registry = registry.as_ref();
+    let found = name
+        .or_else(|| registry.and_then(|r| r.latest.as_deref()))
+        .and_then(|name| Some((name, *registry?.metrics.get(name)?)));
+    let (name, metric) = match found {
+        Some(found) => found,
+        None => {
+            debug!("no distance function registered as {:?}", name);
+            return Err(FFIError::CustomMetricNotFound);
+        }
+    };
+
+    let mut slots = SLOTS.write().unwrap();
+    let bound = slots
+        .iter()
+        .position(|slot| matches!(slot, Some(slot) if slot.name == name));
+    let index = match bound {
+        Some(index) => index,
+        None => {
+            let index = slots
+                .iter()
+                .position(Option::is_none)
+                .ok_or(FFIError::TooManyCustomMetrics)?;
+            slots[index] = Some(Slot {
+                name: name.to_string(),
+                metric,
+                users: 0,
+            });
+            index
+        }
+    };
+    if let Some(slot) = slots[index].as_mut() {
+        slot.users += 1;
+    }
+    Ok(index)
+}
+
+/// Function to let go of a slot held with `bind`
+pub fn release(index: usize) {
+    let mut slots = SLOTS.write().unwrap_or_else(|e| e.into_inner());
+    if let Some(slot) = slots[index].as_mut() {
+        slot.users = slot.users.saturating_sub(1);
+        if slot.users == 0 {
+            slots[index] = None;
+        }
+    }
+}
+
+/// Function to get the `is_expensive` hint of the distance function in a slot
+pub fn is_expensive(index: usize) -> bool {
+    SLOTS.read().unwrap()[index]
+        .as_ref()
+        .map_or(false, |slot| slot.metric.is_expensive)
+}
+
+/// A distance function between two vectors of `f32` elements
+type VecMetric = fn(&Vec<f32>, &Vec<f32>) -> f32;
+
+/// Function to get the copy of `distance` that calls the distance function in a slot
+pub fn function(index: usize) -> VecMetric {
+    let functions: [VecMetric; CUSTOM_SLOTS] = [
+        distance::<0>,
+        distance::<1>,
+        distance::<2>,
+        distance::<3>,
+        distance::<4>,
+        distance::<5>,
+        distance::<6>,
+        distance::<7>,
+    ];
+    functions[index]
+}
+
+/// Function to measure the distance between two vectors with the distance function in a slot
+///
+/// Vectors of different lengths are compared over the length of the shorter one. An empty slot
+/// gives `NaN`, but a tree holds its slot for as long as it can call this.
+fn distance<const SLOT: usize>(x: &Vec<f32>, y: &Vec<f32>) -> f32 {
+    let callback = SLOTS.read().unwrap()[SLOT]
+        .as_ref()
+        .map(|slot| slot.metric.callback);
+    match callback {
+        Some(callback) => callback(x.as_ptr(), y.as_ptr(), x.len().min(y.len()) as i32),
+        None => f32::NAN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern "C" fn manhattan(x: *const f32, y: *const f32, len: i32) -> f32 {
+        let (x, y) = unsafe {
+            (
+                std::slice::from_raw_parts(x, len as usize),
+                std::slice::from_raw_parts(y, len as usize),
+            )
+        };
+        x.iter().zip(y).map(|(a, b)| (a - b).abs()).sum()
+    }
+
+    extern "C" fn zero(_: *const f32, _: *const f32, _: i32) -> f32 {
+        0.0
+    }
+
+    #[test]
+    fn test_custom_metric() {
+        let x = vec![1.0, 2.0, 3.0];
+        let y = vec![4.0, 0.0, 3.0];
+
+        register("test_manhattan", manhattan, true).unwrap();
+        register("test_zero", zero, false).unwrap();
+
+        // With no name a tree takes the most recently registered function
+        let latest = bind(None).unwrap();
+        assert!(!is_expensive(latest));
+        assert_eq!(function(latest)(&x, &y), 0.0);
+
+        let named = bind(Some("test_manhattan")).unwrap();
+        assert!(is_expensive(named));
+        assert_eq!(function(named)(&x, &y), 5.0);
+
+        // A function held by a tree can be neither replaced nor removed
+        assert_eq!(
+            register("test_manhattan", zero, false),
+            Err(FFIError::CustomMetricInUse)
+        );
+        assert_eq!(
+            unregister("test_manhattan"),
+            Err(FFIError::CustomMetricInUse)
+        );
+        assert_eq!(function(named)(&x, &y), 5.0);
+
+        // Trees with the same function share its slot, which is freed once both let go
+        let shared = bind(Some("test_manhattan")).unwrap();
+        assert_eq!(shared, named);
+        release(named);
+        assert_eq!(function(shared)(&x, &y), 5.0);
+        release(shared);
+        unregister("test_manhattan").unwrap();
+        assert_eq!(
+            bind(Some("test_manhattan")),
+            Err(FFIError::CustomMetricNotFound)
+        );
+
+        release(latest);
+        unregister("test_zero").unwrap();
+        assert_eq!(unregister("test_zero"), Err(FFIError::CustomMetricNotFound));
+    }
+}
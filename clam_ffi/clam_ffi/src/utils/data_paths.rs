@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    debug,
+    utils::{csv_readers, error::FFIError, sequence_readers},
+};
+
+/// The resolved locations of the files that make up a dataset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataPaths {
+    pub features: PathBuf,
+    /// The separate labels file, or `None` when the dataset is unlabeled or the labels are a column
+    /// of a delimited features file
+    pub labels: Option<PathBuf>,
+}
+
+impl DataPaths {
+    /// Function to resolve the feature and label files of a dataset
+    ///
+    /// # Arguments
+    ///
+    /// * `data_name` - The name of the dataset
+    /// * `data_path` - Either a data root containing `{data_name}_features.npy`, `{data_name}.csv`,
+    ///   `{data_name}.tsv` or a sequence file such as `{data_name}.fasta`, or a full path to the
+    ///   features file. If `None`, the default data directory is used
+    /// * `labels_path` - A full path to the labels file. If `None`, the labels file is expected
+    ///   next to the features file as `{data_name}_scores.npy`. Sequence files are only labeled
+    ///   when this is given
+    /// * `labeled` - Whether the dataset has labels. If `false`, no labels file is looked for
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the resolved paths or an `FFIError` naming the file that is missing
+    pub fn resolve(
+        data_name: &str,
+        data_path: Option<&Path>,
+        labels_path: Option<&Path>,
+        labeled: bool,
+    ) -> Result<Self, FFIError> {
+        let data_path = match data_path {
+            Some(path) => path.to_path_buf(),
+            None => default_data_dir(),
+        };
+
+        // A directory is treated as the data root, anything else as the features file itself
+        let features = if data_path.is_dir() {
+            let sequence_files = sequence_readers::SEQUENCE_EXTENSIONS
+                .iter()
+                .map(|ext| format!("{data_name}.{ext}"));
+            [
+                format!("{data_name}_features.npy"),
+                format!("{data_name}.csv"),
+                format!("{data_name}.tsv"),
+            ]
+            .into_iter()
+            .chain(sequence_files)
+            .map(|file_name| data_path.join(file_name))
+            .find(|path| path.is_file())
+            .unwrap_or_else(|| data_path.join(format!("{data_name}_features.npy")))
+        } else {
+            data_path
+        };
+
+        if !features.is_file() {
+            debug!("features file not found: {}", features.display());
+            return Err(FFIError::FeaturesFileNotFound);
+        }
+
+        // Delimited files carry their labels in one of the columns
+        if !labeled || csv_readers::is_delimited_file(&features) {
+            return Ok(DataPaths {
+                features,
+                labels: None,
+            });
+        }
+
+        let labels = match labels_path {
+            Some(path) => path.to_path_buf(),
+            None if sequence_readers::is_sequence_file(&features) => {
+                return Ok(DataPaths {
+                    features,
+                    labels: None,
+                })
+            }
+            None => features.with_file_name(format!("{data_name}_scores.npy")),
+        };
+
+        if !labels.is_file() {
+            debug!("labels file not found: {}", labels.display());
+            return Err(FFIError::LabelsFileNotFound);
+        }
+
+        Ok(DataPaths {
+            features,
+            labels: Some(labels),
+        })
+    }
+}
+
+/// Function to get the data directory used when no data path is given
+///
+/// This is `../data/anomaly_data/preprocessed` relative to the current working directory, which
+/// matches the layout of the repository when the host is started from the unity folder.
+pub fn default_data_dir() -> PathBuf {
+    let mut data_dir = std::env::current_dir().unwrap_or_default();
+    data_dir.pop();
+    data_dir.push("data");
+    data_dir.push("anomaly_data");
+    data_dir.push("preprocessed");
+    data_dir
+}
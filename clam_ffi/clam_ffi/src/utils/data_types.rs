@@ -0,0 +1,154 @@
+use std::{fs::File, io::Read, path::Path};
+
+use crate::{debug, utils::error::FFIError};
+
+/// The element type of the instances in a dataset
+#[repr(C)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum DataType {
+    F32,
+    F64,
+    U8,
+    I32,
+    /// Binary vectors, read from `bool` arrays and stored packed into `u64` words
+    Bits,
+    /// Sequences or text, read from FASTA or plain text files
+    String,
+}
+
+impl DataType {
+    /// Function to get an element type from its name, such as `f32` or `string`
+    pub fn from_name(name: &str) -> Result<Self, FFIError> {
+        match name {
+            "f32" => Ok(DataType::F32),
+            "f64" => Ok(DataType::F64),
+            "u8" => Ok(DataType::U8),
+            "i32" => Ok(DataType::I32),
+            "bits" => Ok(DataType::Bits),
+            "string" => Ok(DataType::String),
+            _ => Err(FFIError::UnsupportedDataType),
+        }
+    }
+}
+
+/// Function to find the element type of a `.npy` file from its header
+///
+/// # Arguments
+///
+/// * `path` - The path to the `.npy` file
+///
+/// # Returns
+///
+/// A `Result` containing the element type or an `FFIError` if the file could not be read or holds
+/// an element type that is not supported
+pub fn npy_data_type(path: &Path) -> Result<DataType, FFIError> {
+    let mut file = File::open(path).map_err(|error| {
+        debug!("{:?}", error);
+        FFIError::FeaturesFileNotFound
+    })?;
+
+    // The header is the magic string, a two byte version, the header length and then a python dict
+    let mut preamble = [0u8; 8];
+    file.read_exact(&mut preamble).map_err(|error| {
+        debug!("{:?}", error);
+        FFIError::DataParseFailed
+    })?;
+    if &preamble[..6] != b"\x93NUMPY" {
+        debug!("not a .npy file: {}", path.display());
+        return Err(FFIError::DataParseFailed);
+    }
+
+    let header_len = if preamble[6] == 1 {
+        let mut len = [0u8; 2];
+        file.read_exact(&mut len)
+            .map_err(|_| FFIError::DataParseFailed)?;
+        u16::from_le_bytes(len) as usize
+    } else {
+        let mut len = [0u8; 4];
+        file.read_exact(&mut len)
+            .map_err(|_| FFIError::DataParseFailed)?;
+        u32::from_le_bytes(len) as usize
+    };
+
+    let mut header = vec![0u8; header_len];
+    file.read_exact(&mut header)
+        .map_err(|_| FFIError::DataParseFailed)?;
+    let header = String::from_utf8_lossy(&header);
+
+    let descr = header
+        .split("'descr':")
+        .nth(1)
+        .and_then(|rest| rest.split('\'').nth(1))
+        .ok_or_else(|| {
+            debug!("no dtype in .npy header: {}", header);
+            FFIError::DataParseFailed
+        })?;
+
+    match descr {
+        "<f4" | "=f4" => Ok(DataType::F32),
+        "<f8" | "=f8" => Ok(DataType::F64),
+        "|u1" | "<u1" | "=u1" => Ok(DataType::U8),
+        "<i4" | "=i4" => Ok(DataType::I32),
+        "|b1" => Ok(DataType::Bits),
+        _ => {
+            debug!("unsupported .npy dtype: {}", descr);
+            Err(FFIError::UnsupportedDataType)
+        }
+    }
+}
+
+/// Function to pack a binary vector into `u64` words, lowest bit first
+pub fn pack_bits(bits: &[bool]) -> Vec<u64> {
+    bits.chunks(64)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u64, |word, (i, &bit)| word | ((bit as u64) << i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write_npy_header(name: &str, descr: &str) -> std::path::PathBuf {
+        let header = format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': (0, 3), }}\n");
+        let mut contents = b"\x93NUMPY\x01\x00".to_vec();
+        contents.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        contents.extend_from_slice(header.as_bytes());
+
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_npy_data_type() {
+        let cases = [
+            ("<f4", Ok(DataType::F32)),
+            ("<f8", Ok(DataType::F64)),
+            ("|u1", Ok(DataType::U8)),
+            ("<i4", Ok(DataType::I32)),
+            ("|b1", Ok(DataType::Bits)),
+            ("<c8", Err(FFIError::UnsupportedDataType)),
+        ];
+
+        for (i, (descr, expected)) in cases.into_iter().enumerate() {
+            let path = write_npy_header(&format!("clam_ffi_test_npy_data_type_{i}.npy"), descr);
+            assert_eq!(npy_data_type(&path), expected, "dtype {descr}");
+        }
+    }
+
+    #[test]
+    fn test_pack_bits() {
+        let mut bits = vec![false; 70];
+        bits[0] = true;
+        bits[3] = true;
+        bits[64] = true;
+
+        assert_eq!(pack_bits(&bits), vec![0b1001, 1]);
+    }
+}
@@ -1,8 +1,12 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+use crate::utils::custom_metrics;
 use crate::utils::error::FFIError;
-use distances;
+use distances::{self, Number};
 
 #[repr(C)]
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -18,10 +22,221 @@ pub enum DistanceMetric {
     Canberra,
     NeedlemanWunsch,
     Levenshtein,
+    /// The Lp norm for the `p` the tree is built with
+    Minkowski,
+    Hamming,
+    Jaccard,
+    BrayCurtis,
+    /// One minus the Pearson correlation
+    Correlation,
+    /// A distance function registered by the host. Only `f32` datasets can use it
+    Custom,
+}
+
+/// The `p` of the Minkowski metric when none is given
+pub const DEFAULT_MINKOWSKI_P: f32 = 2.0;
+
+/// The most distinct `p`s the Minkowski metric can be used with by the trees alive at once
+pub const MINKOWSKI_SLOTS: usize = 8;
+
+/// The parameters of the distance metrics that take one
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricParams {
+    /// The `p` of the Minkowski metric
+    pub minkowski_p: f32,
+    /// The name of the registered distance function of the custom metric. `None` uses the most
+    /// recently registered one
+    pub custom_metric: Option<String>,
+}
+
+impl Default for MetricParams {
+    fn default() -> Self {
+        MetricParams {
+            minkowski_p: DEFAULT_MINKOWSKI_P,
+            custom_metric: None,
+        }
+    }
+}
+
+/// The slots a distance function reads its parameters from, held for the tree using it
+///
+/// Datasets need plain function pointers, so each `p` of the Minkowski metric is read by its own
+/// copy of the metric from its own slot. A slot keeps its `p` while any binding holds it, so a tree
+/// keeps the `p` it was built with, and is free for another `p` once every tree using it is dropped.
+/// The custom metric holds the registered function it was built with the same way.
+#[derive(Debug, Default)]
+pub struct MetricBinding {
+    minkowski_slot: Option<usize>,
+    custom_slot: Option<usize>,
+}
+
+impl Drop for MetricBinding {
+    fn drop(&mut self) {
+        if let Some(slot) = self.minkowski_slot {
+            release_minkowski_slot(slot);
+        }
+        if let Some(slot) = self.custom_slot {
+            custom_metrics::release(slot);
+        }
+    }
+}
+
+/// Function to bind the parameters of a distance metric to slots for a tree to hold
+///
+/// # Returns
+///
+/// A `Result` containing the binding, `FFIError::InvalidMetricParameter` if a parameter is out of
+/// range, `FFIError::TooManyMinkowskiParameters` if every Minkowski slot holds another `p`,
+/// `FFIError::CustomMetricNotFound` if the custom metric names no registered function or
+/// `FFIError::TooManyCustomMetrics` if every custom slot holds another function
+pub fn bind(metric: DistanceMetric, params: &MetricParams) -> Result<MetricBinding, FFIError> {
+    let mut binding = MetricBinding::default();
+    match metric {
+        DistanceMetric::Minkowski => {
+            binding.minkowski_slot = Some(bind_minkowski_slot(params.minkowski_p)?);
+        }
+        DistanceMetric::Custom => {
+            binding.custom_slot = Some(custom_metrics::bind(params.custom_metric.as_deref())?);
+        }
+        _ => {}
+    }
+    Ok(binding)
+}
+
+/// The bits of the `p` each copy of the Minkowski metric computes
+static MINKOWSKI_PS: [AtomicU32; MINKOWSKI_SLOTS] = [const { AtomicU32::new(0) }; MINKOWSKI_SLOTS];
+
+/// The number of bindings holding each slot of `MINKOWSKI_PS`. A slot held by none is free
+static MINKOWSKI_USERS: Mutex<[usize; MINKOWSKI_SLOTS]> = Mutex::new([0; MINKOWSKI_SLOTS]);
+
+/// Function to hold the slot of the Minkowski metric that computes `p`, taking a free one if no
+/// slot does
+///
+/// # Returns
+///
+/// The index of the slot, `FFIError::InvalidMetricParameter` if `p` is below 1, where the Lp norm
+/// is not a metric, or `FFIError::TooManyMinkowskiParameters` if every slot holds another `p`
+fn bind_minkowski_slot(p: f32) -> Result<usize, FFIError> {
+    if !(p >= 1.0 && p.is_finite()) {
+        return Err(FFIError::InvalidMetricParameter);
+    }
+
+    let bits = p.to_bits();
+    let mut users = MINKOWSKI_USERS.lock().unwrap_or_else(|e| e.into_inner());
+    let bound = (0..MINKOWSKI_SLOTS)
+        .find(|&slot| users[slot] > 0 && MINKOWSKI_PS[slot].load(Ordering::Acquire) == bits);
+    let slot = match bound {
+        Some(slot) => slot,
+        None => {
+            let slot = users
+                .iter()
+                .position(|&count| count == 0)
+                .ok_or(FFIError::TooManyMinkowskiParameters)?;
+            MINKOWSKI_PS[slot].store(bits, Ordering::Release);
+            slot
+        }
+    };
+    users[slot] += 1;
+    Ok(slot)
+}
+
+/// Function to let go of a slot held with `bind_minkowski_slot`
+fn release_minkowski_slot(slot: usize) {
+    let mut users = MINKOWSKI_USERS.lock().unwrap_or_else(|e| e.into_inner());
+    users[slot] = users[slot].saturating_sub(1);
+}
+
+/// Function to get the `p` a copy of the Minkowski metric computes
+fn slot_p(slot: usize) -> f32 {
+    f32::from_bits(MINKOWSKI_PS[slot].load(Ordering::Acquire))
+}
+
+/// A distance function between two vectors
+type VecMetric<T> = fn(&Vec<T>, &Vec<T>) -> f32;
+
+/// Function to get the copy of the Minkowski metric for vectors of numbers that reads the `p` of
+/// the binding
+fn numeric_minkowski<T: Number>(binding: &MetricBinding) -> Result<VecMetric<T>, FFIError> {
+    let metrics: [VecMetric<T>; MINKOWSKI_SLOTS] = [
+        numeric::minkowski::<T, 0>,
+        numeric::minkowski::<T, 1>,
+        numeric::minkowski::<T, 2>,
+        numeric::minkowski::<T, 3>,
+        numeric::minkowski::<T, 4>,
+        numeric::minkowski::<T, 5>,
+        numeric::minkowski::<T, 6>,
+        numeric::minkowski::<T, 7>,
+    ];
+    binding
+        .minkowski_slot
+        .map(|slot| metrics[slot])
+        .ok_or(FFIError::InvalidMetricParameter)
+}
+
+/// Function to get the copy of the Minkowski metric for packed bit-vectors that reads the `p` of
+/// the binding
+fn bits_minkowski(binding: &MetricBinding) -> Result<VecMetric<u64>, FFIError> {
+    let metrics: [VecMetric<u64>; MINKOWSKI_SLOTS] = [
+        bits::minkowski::<0>,
+        bits::minkowski::<1>,
+        bits::minkowski::<2>,
+        bits::minkowski::<3>,
+        bits::minkowski::<4>,
+        bits::minkowski::<5>,
+        bits::minkowski::<6>,
+        bits::minkowski::<7>,
+    ];
+    binding
+        .minkowski_slot
+        .map(|slot| metrics[slot])
+        .ok_or(FFIError::InvalidMetricParameter)
+}
+
+/// Function to check if a distance metric is expensive
+///
+/// A custom metric is expensive if the host said so when registering the function it is bound to,
+/// whatever `is_expensive` is set to
+pub fn is_expensive(binding: &MetricBinding, is_expensive: bool) -> bool {
+    is_expensive
+        || binding
+            .custom_slot
+            .is_some_and(custom_metrics::is_expensive)
+}
+
+/// Function to get a distance metric from its snake case name, such as `euclidean_sq`
+///
+/// Custom metrics are registered by a host and have no name here
+pub fn from_name(name: &str) -> Result<DistanceMetric, FFIError> {
+    match name {
+        "euclidean" => Ok(DistanceMetric::Euclidean),
+        "euclidean_sq" => Ok(DistanceMetric::EuclideanSQ),
+        "manhattan" => Ok(DistanceMetric::Manhattan),
+        "l3" => Ok(DistanceMetric::L3Norm),
+        "l4" => Ok(DistanceMetric::L4Norm),
+        "chebyshev" => Ok(DistanceMetric::Chebyshev),
+        "cosine" => Ok(DistanceMetric::Cosine),
+        "canberra" => Ok(DistanceMetric::Canberra),
+        "needleman_wunsch" => Ok(DistanceMetric::NeedlemanWunsch),
+        "levenshtein" => Ok(DistanceMetric::Levenshtein),
+        "minkowski" => Ok(DistanceMetric::Minkowski),
+        "hamming" => Ok(DistanceMetric::Hamming),
+        "jaccard" => Ok(DistanceMetric::Jaccard),
+        "bray_curtis" => Ok(DistanceMetric::BrayCurtis),
+        "correlation" => Ok(DistanceMetric::Correlation),
+        _ => Err(FFIError::UnsupportedMetric),
+    }
 }
 
-//TODO: Make generic for strings as well
-pub fn from_enum(metric: DistanceMetric) -> Result<fn(&Vec<f32>, &Vec<f32>) -> f32, FFIError> {
+/// Function to get the distance function for vectors of `f32` elements
+///
+/// # Arguments
+///
+/// * `metric` - The distance metric
+/// * `binding` - The slots of the parameters of the metric, from `bind`
+pub fn from_enum(
+    metric: DistanceMetric,
+    binding: &MetricBinding,
+) -> Result<fn(&Vec<f32>, &Vec<f32>) -> f32, FFIError> {
     match metric {
         DistanceMetric::Euclidean => Ok(euclidean),
         DistanceMetric::EuclideanSQ => Ok(euclidean_sq),
@@ -31,6 +246,15 @@ pub fn from_enum(metric: DistanceMetric) -> Result<fn(&Vec<f32>, &Vec<f32>) -> f
         DistanceMetric::Chebyshev => Ok(chebyshev),
         DistanceMetric::Cosine => Ok(cosine),
         DistanceMetric::Canberra => Ok(canberra),
+        DistanceMetric::Minkowski => numeric_minkowski(binding),
+        DistanceMetric::Hamming => Ok(numeric::hamming),
+        DistanceMetric::Jaccard => Ok(numeric::jaccard),
+        DistanceMetric::BrayCurtis => Ok(numeric::bray_curtis),
+        DistanceMetric::Correlation => Ok(numeric::correlation),
+        DistanceMetric::Custom => binding
+            .custom_slot
+            .map(custom_metrics::function)
+            .ok_or(FFIError::CustomMetricNotFound),
 
         // Handle unsupported or unimplemented metrics as an error
         _ => Err(FFIError::UnsupportedMetric),
@@ -51,7 +275,7 @@ pub fn l3_norm(x: &Vec<f32>, y: &Vec<f32>) -> f32 {
     distances::vectors::l3_norm(x, y)
 }
 pub fn l4_norm(x: &Vec<f32>, y: &Vec<f32>) -> f32 {
-    distances::vectors::l3_norm(x, y)
+    distances::vectors::l4_norm(x, y)
 }
 pub fn chebyshev(x: &Vec<f32>, y: &Vec<f32>) -> f32 {
     distances::vectors::chebyshev(x, y)
@@ -64,12 +288,455 @@ pub fn canberra(x: &Vec<f32>, y: &Vec<f32>) -> f32 {
     distances::vectors::canberra(x, y)
 }
 
+/// Function to get the distance function for vectors of `f64`, `u8` or `i32` elements
+///
+/// The differences are taken in `f64`, so integer vectors cannot wrap around or overflow
+///
+/// # Arguments
+///
+/// * `metric` - The distance metric
+/// * `binding` - The slots of the parameters of the metric, from `bind`
+pub fn numeric_from_enum<T: Number>(
+    metric: DistanceMetric,
+    binding: &MetricBinding,
+) -> Result<fn(&Vec<T>, &Vec<T>) -> f32, FFIError> {
+    match metric {
+        DistanceMetric::Euclidean => Ok(numeric::euclidean),
+        DistanceMetric::EuclideanSQ => Ok(numeric::euclidean_sq),
+        DistanceMetric::Manhattan => Ok(numeric::manhattan),
+        DistanceMetric::L3Norm => Ok(numeric::l3_norm),
+        DistanceMetric::L4Norm => Ok(numeric::l4_norm),
+        DistanceMetric::Chebyshev => Ok(numeric::chebyshev),
+        DistanceMetric::Cosine => Ok(numeric::cosine),
+        DistanceMetric::Canberra => Ok(numeric::canberra),
+        DistanceMetric::Minkowski => numeric_minkowski(binding),
+        DistanceMetric::Hamming => Ok(numeric::hamming),
+        DistanceMetric::Jaccard => Ok(numeric::jaccard),
+        DistanceMetric::BrayCurtis => Ok(numeric::bray_curtis),
+        DistanceMetric::Correlation => Ok(numeric::correlation),
+
+        _ => Err(FFIError::UnsupportedMetric),
+    }
+}
+
+/// Function to get the distance function for packed bit-vectors
+///
+/// On binary data the Manhattan and squared Euclidean distances are both the Hamming distance
+///
+/// # Arguments
+///
+/// * `metric` - The distance metric
+/// * `binding` - The slots of the parameters of the metric, from `bind`
+pub fn bits_from_enum(
+    metric: DistanceMetric,
+    binding: &MetricBinding,
+) -> Result<fn(&Vec<u64>, &Vec<u64>) -> f32, FFIError> {
+    match metric {
+        DistanceMetric::Euclidean => Ok(bits::euclidean),
+        DistanceMetric::EuclideanSQ | DistanceMetric::Manhattan => Ok(bits::hamming),
+        DistanceMetric::Chebyshev => Ok(bits::chebyshev),
+        DistanceMetric::Cosine => Ok(bits::cosine),
+        DistanceMetric::Minkowski => bits_minkowski(binding),
+        DistanceMetric::Hamming => Ok(bits::hamming),
+        DistanceMetric::Jaccard => Ok(bits::jaccard),
+        DistanceMetric::BrayCurtis => Ok(bits::bray_curtis),
+
+        _ => Err(FFIError::UnsupportedMetric),
+    }
+}
+
+pub mod numeric {
+    use distances::Number;
+
+    fn abs_diffs<'a, T: Number>(x: &'a [T], y: &'a [T]) -> impl Iterator<Item = f64> + 'a {
+        x.iter()
+            .zip(y)
+            .map(|(&a, &b)| (a.as_f64() - b.as_f64()).abs())
+    }
+
+    pub fn euclidean<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        abs_diffs(x, y).map(|d| d * d).sum::<f64>().sqrt() as f32
+    }
+    pub fn euclidean_sq<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        abs_diffs(x, y).map(|d| d * d).sum::<f64>() as f32
+    }
+    pub fn manhattan<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        abs_diffs(x, y).sum::<f64>() as f32
+    }
+    pub fn l3_norm<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        abs_diffs(x, y).map(|d| d.powi(3)).sum::<f64>().cbrt() as f32
+    }
+    pub fn l4_norm<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        abs_diffs(x, y).map(|d| d.powi(4)).sum::<f64>().powf(0.25) as f32
+    }
+    pub fn chebyshev<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        abs_diffs(x, y).fold(0.0, f64::max) as f32
+    }
+    pub fn cosine<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        let (mut xy, mut xx, mut yy) = (0.0, 0.0, 0.0);
+        for (&a, &b) in x.iter().zip(y) {
+            let (a, b) = (a.as_f64(), b.as_f64());
+            xy += a * b;
+            xx += a * a;
+            yy += b * b;
+        }
+        if xx == 0.0 || yy == 0.0 {
+            return if xx == yy { 0.0 } else { 1.0 };
+        }
+        (1.0 - xy / (xx.sqrt() * yy.sqrt())).max(0.0) as f32
+    }
+    pub fn canberra<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        x.iter()
+            .zip(y)
+            .map(|(&a, &b)| {
+                let (a, b) = (a.as_f64(), b.as_f64());
+                let denominator = a.abs() + b.abs();
+                if denominator == 0.0 {
+                    0.0
+                } else {
+                    (a - b).abs() / denominator
+                }
+            })
+            .sum::<f64>() as f32
+    }
+    /// The Lp norm for the `p` bound to `SLOT`
+    pub fn minkowski<T: Number, const SLOT: usize>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        let p = super::slot_p(SLOT) as f64;
+        abs_diffs(x, y)
+            .map(|d| d.powf(p))
+            .sum::<f64>()
+            .powf(1.0 / p) as f32
+    }
+    /// The number of positions where the vectors differ
+    pub fn hamming<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        x.iter().zip(y).filter(|(a, b)| a != b).count() as f32
+    }
+    /// The weighted Jaccard distance for non-negative data. On 0/1 data this is the Jaccard
+    /// distance between the sets of non-zero positions
+    pub fn jaccard<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        let (mut mins, mut maxes) = (0.0, 0.0);
+        for (&a, &b) in x.iter().zip(y) {
+            let (a, b) = (a.as_f64(), b.as_f64());
+            mins += a.min(b);
+            maxes += a.max(b);
+        }
+        if maxes == 0.0 {
+            0.0
+        } else {
+            (1.0 - mins / maxes) as f32
+        }
+    }
+    pub fn bray_curtis<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        let (mut differences, mut sums) = (0.0, 0.0);
+        for (&a, &b) in x.iter().zip(y) {
+            let (a, b) = (a.as_f64(), b.as_f64());
+            differences += (a - b).abs();
+            sums += (a + b).abs();
+        }
+        if sums == 0.0 {
+            0.0
+        } else {
+            (differences / sums) as f32
+        }
+    }
+    pub fn correlation<T: Number>(x: &Vec<T>, y: &Vec<T>) -> f32 {
+        let n = x.len().min(y.len()) as f64;
+        if n == 0.0 {
+            return 0.0;
+        }
+        let mean_x = x.iter().map(|a| a.as_f64()).sum::<f64>() / n;
+        let mean_y = y.iter().map(|b| b.as_f64()).sum::<f64>() / n;
+
+        let (mut xy, mut xx, mut yy) = (0.0, 0.0, 0.0);
+        for (&a, &b) in x.iter().zip(y) {
+            let (a, b) = (a.as_f64() - mean_x, b.as_f64() - mean_y);
+            xy += a * b;
+            xx += a * a;
+            yy += b * b;
+        }
+        // A constant vector has no correlation with anything but another constant vector
+        if xx == 0.0 || yy == 0.0 {
+            return if xx == yy { 0.0 } else { 1.0 };
+        }
+        (1.0 - xy / (xx.sqrt() * yy.sqrt())).clamp(0.0, 2.0) as f32
+    }
+}
+
+pub mod bits {
+    fn count_ones<'a>(words: impl Iterator<Item = u64> + 'a) -> u32 {
+        words.map(u64::count_ones).sum()
+    }
+
+    pub fn hamming(x: &Vec<u64>, y: &Vec<u64>) -> f32 {
+        count_ones(x.iter().zip(y).map(|(a, b)| a ^ b)) as f32
+    }
+    pub fn euclidean(x: &Vec<u64>, y: &Vec<u64>) -> f32 {
+        hamming(x, y).sqrt()
+    }
+    pub fn chebyshev(x: &Vec<u64>, y: &Vec<u64>) -> f32 {
+        if x == y {
+            0.0
+        } else {
+            1.0
+        }
+    }
+    pub fn cosine(x: &Vec<u64>, y: &Vec<u64>) -> f32 {
+        let xy = count_ones(x.iter().zip(y).map(|(a, b)| a & b)) as f32;
+        let xx = count_ones(x.iter().copied()) as f32;
+        let yy = count_ones(y.iter().copied()) as f32;
+        if xx == 0.0 || yy == 0.0 {
+            return if xx == yy { 0.0 } else { 1.0 };
+        }
+        (1.0 - xy / (xx * yy).sqrt()).max(0.0)
+    }
+    /// The Lp norm for the `p` bound to `SLOT`
+    pub fn minkowski<const SLOT: usize>(x: &Vec<u64>, y: &Vec<u64>) -> f32 {
+        hamming(x, y).powf(1.0 / super::slot_p(SLOT))
+    }
+    pub fn jaccard(x: &Vec<u64>, y: &Vec<u64>) -> f32 {
+        let intersection = count_ones(x.iter().zip(y).map(|(a, b)| a & b)) as f32;
+        let union = count_ones(x.iter().zip(y).map(|(a, b)| a | b)) as f32;
+        if union == 0.0 {
+            0.0
+        } else {
+            1.0 - intersection / union
+        }
+    }
+    pub fn bray_curtis(x: &Vec<u64>, y: &Vec<u64>) -> f32 {
+        let sums = (count_ones(x.iter().copied()) + count_ones(y.iter().copied())) as f32;
+        if sums == 0.0 {
+            0.0
+        } else {
+            hamming(x, y) / sums
+        }
+    }
+}
+
+/// Function to get the distance function for string datasets
+pub fn string_from_enum(metric: DistanceMetric) -> Result<fn(&String, &String) -> f32, FFIError> {
+    match metric {
+        DistanceMetric::NeedlemanWunsch => Ok(nw_distance),
+        DistanceMetric::Levenshtein => Ok(levenshtein),
+
+        _ => Err(FFIError::UnsupportedMetric),
+    }
+}
+
 //Needleman Wunsch
-pub fn nw_distance(x: &str, y: &str) -> u32 {
-    distances::strings::nw_distance(x, y)
+pub fn nw_distance(x: &String, y: &String) -> f32 {
+    let distance: u32 = distances::strings::nw_distance(x, y);
+    distance as f32
 }
 
 //levenshtein
-pub fn levenshtein(x: &str, y: &str) -> u32 {
-    distances::strings::levenshtein(x, y)
+pub fn levenshtein(x: &String, y: &String) -> f32 {
+    let distance: u32 = distances::strings::levenshtein(x, y);
+    distance as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    fn naive_lp(x: &[f32], y: &[f32], p: f64) -> f64 {
+        let mut sum = 0.0;
+        for i in 0..x.len() {
+            sum += ((x[i] - y[i]) as f64).abs().powf(p);
+        }
+        sum.powf(1.0 / p)
+    }
+
+    fn naive_hamming(x: &[f32], y: &[f32]) -> f64 {
+        let mut count = 0.0;
+        for i in 0..x.len() {
+            if x[i] != y[i] {
+                count += 1.0;
+            }
+        }
+        count
+    }
+
+    fn naive_jaccard(x: &[f32], y: &[f32]) -> f64 {
+        let (mut mins, mut maxes) = (0.0, 0.0);
+        for i in 0..x.len() {
+            mins += x[i].min(y[i]) as f64;
+            maxes += x[i].max(y[i]) as f64;
+        }
+        1.0 - mins / maxes
+    }
+
+    fn naive_bray_curtis(x: &[f32], y: &[f32]) -> f64 {
+        let (mut differences, mut sums) = (0.0, 0.0);
+        for i in 0..x.len() {
+            differences += ((x[i] - y[i]) as f64).abs();
+            sums += ((x[i] + y[i]) as f64).abs();
+        }
+        differences / sums
+    }
+
+    fn naive_correlation(x: &[f32], y: &[f32]) -> f64 {
+        let n = x.len() as f64;
+        let mean_x = x.iter().map(|&a| a as f64).sum::<f64>() / n;
+        let mean_y = y.iter().map(|&b| b as f64).sum::<f64>() / n;
+        let (mut covariance, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+        for i in 0..x.len() {
+            covariance += (x[i] as f64 - mean_x) * (y[i] as f64 - mean_y);
+            var_x += (x[i] as f64 - mean_x).powi(2);
+            var_y += (y[i] as f64 - mean_y).powi(2);
+        }
+        1.0 - covariance / (var_x * var_y).sqrt()
+    }
+
+    fn random_pairs() -> Vec<(Vec<f32>, Vec<f32>)> {
+        let mut rng = StdRng::seed_from_u64(42);
+        (0..20)
+            .map(|_| {
+                let x = (0..16).map(|_| rng.gen_range(0.0..10.0)).collect();
+                let y = (0..16).map(|_| rng.gen_range(0.0..10.0)).collect();
+                (x, y)
+            })
+            .collect()
+    }
+
+    fn assert_close(actual: f32, expected: f64, metric: DistanceMetric) {
+        let tolerance = 1e-4 * expected.abs().max(1.0);
+        assert!(
+            (actual as f64 - expected).abs() <= tolerance,
+            "{:?}: {} != {}",
+            metric,
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_metrics_against_naive() {
+        let references: [(DistanceMetric, fn(&[f32], &[f32]) -> f64); 8] = [
+            (DistanceMetric::Euclidean, |x, y| naive_lp(x, y, 2.0)),
+            (DistanceMetric::Manhattan, |x, y| naive_lp(x, y, 1.0)),
+            (DistanceMetric::L3Norm, |x, y| naive_lp(x, y, 3.0)),
+            (DistanceMetric::L4Norm, |x, y| naive_lp(x, y, 4.0)),
+            (DistanceMetric::Hamming, naive_hamming),
+            (DistanceMetric::Jaccard, naive_jaccard),
+            (DistanceMetric::BrayCurtis, naive_bray_curtis),
+            (DistanceMetric::Correlation, naive_correlation),
+        ];
+
+        let binding = MetricBinding::default();
+        for (x, y) in random_pairs() {
+            for (metric, reference) in references {
+                let expected = reference(&x, &y);
+                assert_close(
+                    from_enum(metric, &binding).unwrap()(&x, &y),
+                    expected,
+                    metric,
+                );
+                assert_close(
+                    numeric_from_enum::<f32>(metric, &binding).unwrap()(&x, &y),
+                    expected,
+                    metric,
+                );
+            }
+        }
+    }
+
+    fn bind_p(p: f32) -> Result<MetricBinding, FFIError> {
+        let params = MetricParams {
+            minkowski_p: p,
+            ..Default::default()
+        };
+        bind(DistanceMetric::Minkowski, &params)
+    }
+
+    #[test]
+    fn test_minkowski_p() {
+        let metric = DistanceMetric::Minkowski;
+        assert_eq!(bind_p(0.5).err(), Some(FFIError::InvalidMetricParameter));
+
+        let ps = [1.0, 1.5, 3.0];
+        let bindings: Vec<_> = ps.iter().map(|&p| bind_p(p).unwrap()).collect();
+        // Binding a new `p` does not change the metric of the ones bound before it
+        for (&p, binding) in ps.iter().zip(&bindings) {
+            let minkowski = from_enum(metric, binding).unwrap();
+            for (x, y) in random_pairs() {
+                assert_close(minkowski(&x, &y), naive_lp(&x, &y, p as f64), metric);
+            }
+        }
+
+        // A tree with the same `p` shares the slot, which keeps its `p` while either is alive
+        let shared = bind_p(1.5).unwrap();
+        assert_eq!(shared.minkowski_slot, bindings[1].minkowski_slot);
+        drop(bindings);
+        let minkowski = from_enum(metric, &shared).unwrap();
+        let (x, y) = &random_pairs()[0];
+        assert_close(minkowski(x, y), naive_lp(x, y, 1.5), metric);
+    }
+
+    #[test]
+    fn test_minkowski_slots_are_released() {
+        // Dropping a binding frees its slot, so any number of `p`s can be used one after another
+        for i in 0..4 * MINKOWSKI_SLOTS {
+            let p = 5.0 + i as f32 * 0.25;
+            let binding = bind_p(p).unwrap();
+            let slot = binding.minkowski_slot.unwrap();
+            assert_eq!(slot_p(slot), p);
+        }
+    }
+
+    #[test]
+    fn test_bit_metrics_against_naive() {
+        let x_bits: Vec<bool> = (0..100).map(|i| i % 3 == 0).collect();
+        let y_bits: Vec<bool> = (0..100).map(|i| i % 5 == 0).collect();
+        let x_floats: Vec<f32> = x_bits.iter().map(|&bit| bit as u8 as f32).collect();
+        let y_floats: Vec<f32> = y_bits.iter().map(|&bit| bit as u8 as f32).collect();
+        let x = crate::utils::data_types::pack_bits(&x_bits);
+        let y = crate::utils::data_types::pack_bits(&y_bits);
+
+        let metric = DistanceMetric::Hamming;
+        assert_close(
+            bits::hamming(&x, &y),
+            naive_hamming(&x_floats, &y_floats),
+            metric,
+        );
+        let metric = DistanceMetric::Jaccard;
+        assert_close(
+            bits::jaccard(&x, &y),
+            naive_jaccard(&x_floats, &y_floats),
+            metric,
+        );
+        let metric = DistanceMetric::BrayCurtis;
+        assert_close(
+            bits::bray_curtis(&x, &y),
+            naive_bray_curtis(&x_floats, &y_floats),
+            metric,
+        );
+    }
+
+    #[test]
+    fn test_numeric_integer_vectors() {
+        let x: Vec<u8> = vec![0, 255, 10];
+        let y: Vec<u8> = vec![255, 0, 10];
+
+        assert_eq!(numeric::manhattan(&x, &y), 510.0);
+        assert_eq!(numeric::euclidean_sq(&x, &y), 2.0 * 255.0 * 255.0);
+        assert_eq!(numeric::chebyshev(&x, &y), 255.0);
+
+        let x: Vec<i32> = vec![-3, 0];
+        let y: Vec<i32> = vec![0, 4];
+        assert_eq!(numeric::euclidean(&x, &y), 5.0);
+        assert_eq!(numeric::l4_norm(&vec![0i32, 0], &vec![2i32, 0]), 2.0);
+    }
+
+    #[test]
+    fn test_bit_vectors() {
+        let x = vec![0b1011u64, 1];
+        let y = vec![0b0010u64, 0];
+
+        assert_eq!(bits::hamming(&x, &y), 3.0);
+        assert_eq!(bits::euclidean(&x, &y), 3f32.sqrt());
+        assert_eq!(bits::chebyshev(&x, &y), 1.0);
+        assert_eq!(bits::cosine(&x, &x), 0.0);
+        assert_eq!(bits::cosine(&x, &vec![0, 0]), 1.0);
+    }
 }
@@ -24,4 +24,30 @@ pub enum FFIError {
     NotInCache,
     TooManyLabels,
     ColoringFailed,
+    FeaturesFileNotFound,
+    LabelsFileNotFound,
+    DataParseFailed,
+    NoLabels,
+    UnsupportedDataType,
+    InvalidMetricParameter,
+    CustomMetricNotFound,
+    NoGraphLayout,
+    LoadLayoutFailed,
+    LayoutMismatch,
+    GraphSelectionNotFound,
+    UnsupportedGraphFormat,
+    UnsupportedMeshFormat,
+    UnsupportedImageFormat,
+    RenderFailed,
+    NoDistanceCacheStats,
+    InvalidPhysicsSettings,
+    NoEnergyHistory,
+    PhysicsPaused,
+    NoPhysicsToRestart,
+    NodeNotPinned,
+    TooManyMinkowskiParameters,
+    NodeNotInGraph,
+    CustomMetricInUse,
+    TooManyCustomMetrics,
+    BufferTooSmall,
 }
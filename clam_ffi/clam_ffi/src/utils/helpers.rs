@@ -1,5 +1,7 @@
 use std::{ffi::{c_char, CStr}, fs::OpenOptions, io::{self, Write}};
 
+use rand::{rngs::StdRng, SeedableRng};
+
 use super::error::FFIError;
 
 pub fn append_to_file(filename: &str, content: &str) -> Result<(), io::Error> {
@@ -89,3 +91,19 @@ pub unsafe fn csharp_to_rust_utf8(utf8_str: *const u8, utf8_len: i32) -> Result<
         Err(_) => Err(FFIError::InvalidStringPassed),
     }
 }
+
+/// Function to create a random number generator from an optional seed
+///
+/// # Arguments
+///
+/// * `seed` - The seed to use. `None` seeds the generator from the operating system
+///
+/// # Returns
+///
+/// A `StdRng` that gives the same numbers every time it is created with the same seed
+pub fn seeded_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
@@ -1,4 +1,10 @@
 pub mod anomaly_readers;
+pub mod clam_tree;
+pub mod coloring;
+pub mod csv_readers;
+pub mod custom_metrics;
+pub mod data_paths;
+pub mod data_types;
 // pub mod debug;
 #[macro_use]
 pub mod debug;
@@ -6,4 +12,5 @@ pub mod distances;
 pub mod error;
 pub mod helpers;
 pub mod scoring_functions;
+pub mod sequence_readers;
 pub mod types;
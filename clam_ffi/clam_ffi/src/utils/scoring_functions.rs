@@ -1,7 +1,7 @@
 use super::error::FFIError;
 
 #[repr(C)]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum ScoringFunction {
     LrManhattanSc,
     LrManhattanCc,
@@ -59,6 +59,37 @@ pub fn enum_to_string(scoring_function: &ScoringFunction) -> Result<String, FFIE
     }
 }
 
+/// Function to get the scoring function named by `enum_to_string`
+pub fn string_to_enum(name: &str) -> Result<ScoringFunction, FFIError> {
+    match name {
+        "lr_manhattan_sc" => Ok(ScoringFunction::LrManhattanSc),
+        "lr_manhattan_cc" => Ok(ScoringFunction::LrManhattanCc),
+        "lr_manhattan_gn" => Ok(ScoringFunction::LrManhattanGn),
+        "lr_manhattan_cr" => Ok(ScoringFunction::LrManhattanCr),
+        "lr_manhattan_sp" => Ok(ScoringFunction::LrManhattanSp),
+        "lr_manhattan_vd" => Ok(ScoringFunction::LrManhattanVd),
+        "lr_euclidean_cc" => Ok(ScoringFunction::LrEuclideanCc),
+        "lr_euclidean_sc" => Ok(ScoringFunction::LrEuclideanSc),
+        "lr_euclidean_gn" => Ok(ScoringFunction::LrEuclideanGn),
+        "lr_euclidean_cr" => Ok(ScoringFunction::LrEuclideanCr),
+        "lr_euclidean_sp" => Ok(ScoringFunction::LrEuclideanSp),
+        "lr_euclidean_vd" => Ok(ScoringFunction::LrEuclideanVd),
+        "dt_manhattan_cc" => Ok(ScoringFunction::DtManhattanCc),
+        "dt_manhattan_sc" => Ok(ScoringFunction::DtManhattanSc),
+        "dt_manhattan_gn" => Ok(ScoringFunction::DtManhattanGn),
+        "dt_manhattan_cr" => Ok(ScoringFunction::DtManhattanCr),
+        "dt_manhattan_sp" => Ok(ScoringFunction::DtManhattanSp),
+        "dt_manhattan_vd" => Ok(ScoringFunction::DtManhattanVd),
+        "dt_euclidean_cc" => Ok(ScoringFunction::DtEuclideanCc),
+        "dt_euclidean_sc" => Ok(ScoringFunction::DtEuclideanSc),
+        "dt_euclidean_gn" => Ok(ScoringFunction::DtEuclideanGn),
+        "dt_euclidean_cr" => Ok(ScoringFunction::DtEuclideanCr),
+        "dt_euclidean_sp" => Ok(ScoringFunction::DtEuclideanSp),
+        "dt_euclidean_vd" => Ok(ScoringFunction::DtEuclideanVd),
+        _ => Err(FFIError::ScoringFunctionNotFound),
+    }
+}
+
 pub fn enum_to_function(
     scoring_function: &ScoringFunction,
 ) -> Result<abd_clam::graph::MetaMLScorer, FFIError> {
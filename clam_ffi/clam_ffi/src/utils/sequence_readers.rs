@@ -0,0 +1,101 @@
+use std::{fs, path::Path};
+
+use crate::utils::error::FFIError;
+
+/// The extensions of sequence files. FASTA files end in `.fasta`, `.fa`, `.fna` or `.faa`. Plain
+/// text files end in `.txt` and hold one sequence per line.
+pub const SEQUENCE_EXTENSIONS: [&str; 5] = ["fasta", "fa", "fna", "faa", "txt"];
+
+/// Function to check if a path points to a sequence file, by its extension
+pub fn is_sequence_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map_or(false, |ext| SEQUENCE_EXTENSIONS.contains(&ext))
+}
+
+/// Function to read the sequences from a FASTA or plain text file
+///
+/// # Arguments
+///
+/// * `path` - The path to the sequence file
+///
+/// # Returns
+///
+/// A `Result` containing one `String` per sequence or an `FFIError` if the file could not be read
+/// or holds no sequences
+pub fn read_sequences(path: &Path) -> Result<Vec<String>, FFIError> {
+    if !path.is_file() {
+        debug!("features file not found: {}", path.display());
+        return Err(FFIError::FeaturesFileNotFound);
+    }
+
+    let contents = fs::read_to_string(path).map_err(|error| {
+        debug!("{:?}", error);
+        FFIError::DataParseFailed
+    })?;
+
+    let sequences = if contents.trim_start().starts_with('>') {
+        parse_fasta(&contents)
+    } else {
+        contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+
+    if sequences.is_empty() {
+        debug!("no sequences in {}", path.display());
+        return Err(FFIError::DataParseFailed);
+    }
+
+    Ok(sequences)
+}
+
+/// Function to join the sequence lines of each FASTA record, dropping the `>` header lines
+fn parse_fasta(contents: &str) -> Vec<String> {
+    let mut sequences = Vec::new();
+    let mut current: Option<String> = None;
+
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('>') {
+            sequences.extend(current.take());
+            current = Some(String::new());
+        } else if line.starts_with(';') || line.is_empty() {
+            continue;
+        } else if let Some(sequence) = current.as_mut() {
+            sequence.push_str(line);
+        }
+    }
+    sequences.extend(current);
+
+    sequences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_fasta() {
+        let path = std::env::temp_dir().join("clam_ffi_test_read_fasta.fasta");
+        fs::write(&path, ">seq1 first\nACGT\nAC\n\n>seq2\n;comment\nGGTA\n").unwrap();
+
+        assert_eq!(
+            read_sequences(&path).unwrap(),
+            vec!["ACGTAC".to_string(), "GGTA".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_read_plain_text() {
+        let path = std::env::temp_dir().join("clam_ffi_test_read_plain_text.txt");
+        fs::write(&path, "kitten\n\nsitting\n").unwrap();
+
+        assert_eq!(
+            read_sequences(&path).unwrap(),
+            vec!["kitten".to_string(), "sitting".to_string()]
+        );
+    }
+}
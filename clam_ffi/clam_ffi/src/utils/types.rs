@@ -1,10 +1,13 @@
-use abd_clam::{graph::{Graph, Vertex}, Tree, VecDataset};
+use abd_clam::{
+    graph::{Graph, Vertex},
+    Tree, VecDataset,
+};
 
 use crate::handle::handle::Handle;
 
-pub type OutHandlePtr<'a> = Option<&'a mut *mut Handle<'a>>;
+pub type OutHandlePtr<'a> = Option<&'a mut *mut Handle>;
 
-pub type InHandlePtr<'a> = Option<&'a mut Handle<'a>>;
+pub type InHandlePtr<'a> = Option<&'a mut Handle>;
 
 // pub type Clusterf32 = Cluster<f32>;
 // pub type DataSetf32 = VecDataset<Vec<f32>, f32, u8>;
@@ -12,5 +15,16 @@ pub type InHandlePtr<'a> = Option<&'a mut Handle<'a>>;
 pub type Vertexf32 = Vertex<f32>;
 pub type DataSetf32 = VecDataset<Vec<f32>, f32, u8>;
 pub type Treef32 = Tree<Vec<f32>, f32, DataSetf32, Vertexf32>;
+pub type DataSetf64 = VecDataset<Vec<f64>, f32, u8>;
+pub type Treef64 = Tree<Vec<f64>, f32, DataSetf64, Vertexf32>;
+pub type DataSetu8 = VecDataset<Vec<u8>, f32, u8>;
+pub type Treeu8 = Tree<Vec<u8>, f32, DataSetu8, Vertexf32>;
+pub type DataSeti32 = VecDataset<Vec<i32>, f32, u8>;
+pub type Treei32 = Tree<Vec<i32>, f32, DataSeti32, Vertexf32>;
+/// Bit-vectors packed 64 bits to a word, lowest bit first
+pub type DataSetBits = VecDataset<Vec<u64>, f32, u8>;
+pub type TreeBits = Tree<Vec<u64>, f32, DataSetBits, Vertexf32>;
+pub type DataSetString = VecDataset<String, f32, u8>;
+pub type TreeString = Tree<String, f32, DataSetString, Vertexf32>;
 pub type Graphf32<'a> = Graph<'a, f32>;
 // pub type Cakesf32 = Cakes<f32, f32, DataSet>;
@@ -12,6 +12,37 @@ public class TreeStartupData : ScriptableObject
     public uint cardinality;
     public bool isExpensive;
     public bool shouldLoad;
+    // Either a data root or a full path to the features file. Empty uses the default data root
+    public string dataPath;
+    // A full path to the labels file. Empty looks next to the features file
+    public string labelsPath;
+    // The one-based column of a .csv/.tsv file holding the labels. Negative values count from the
+    // end, and zero is the last column
+    public int labelColumn;
+    // The field delimiter of a .csv/.tsv file. '\0' picks one from the file extension
+    public char delimiter;
+    // Whether the first row of a .csv/.tsv file is data rather than a header
+    public bool noHeader;
+    // Whether the dataset has no labels. If set, no labels file or label column is read
+    public bool featuresOnly;
+    // The element type of a saved tree. Built trees take the element type of their .npy file
+    public DataType dataType;
+    // The p of the Minkowski metric. Zero is 2
+    public float minkowskiP;
+    // The name of a registered distance function, read when the metric is Custom. Empty uses the
+    // most recently registered function
+    public string customMetric;
+    // Whether a cluster is partitioned if it passes any criterion rather than every one
+    public bool checkAny;
+    // The deepest a cluster may be and still be partitioned. Zero is unlimited
+    public int maxDepth;
+    // The smallest radius a cluster may have and still be partitioned. Zero is unchecked
+    public float minRadius;
+    // The smallest local fractal dimension a cluster may have and still be partitioned. Zero is
+    // unchecked
+    public float minLfd;
+    // The seed for partitioning and the graph layout. Negative values use no seed
+    public long seed;
     // Start is called before the first frame update
     void Start()
     {
@@ -0,0 +1,13 @@
+namespace Clam
+{
+    // The element type of the instances in a dataset. Must match DataType in clam_ffi
+    public enum DataType
+    {
+        F32,
+        F64,
+        U8,
+        I32,
+        Bits,
+        String,
+    }
+}
\ No newline at end of file
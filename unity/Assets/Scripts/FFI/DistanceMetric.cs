@@ -14,5 +14,11 @@ namespace Clam
         Canberra,
         NeedlemanWunsch,
         Levenshtein,
+        Minkowski,
+        Hamming,
+        Jaccard,
+        BrayCurtis,
+        Correlation,
+        Custom,
     }
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+using System.Runtime.InteropServices;
+
+namespace Clam
+{
+    namespace FFI
+    {
+        // The enums and option structs below must match their namesakes in clam_ffi
+
+        public enum GraphFormat
+        {
+            GraphMl,
+            Gexf,
+            Dot,
+        }
+
+        public enum MeshFormat
+        {
+            Gltf,
+            Obj,
+            Ply,
+        }
+
+        public enum ImageFormat
+        {
+            Svg,
+            Png,
+        }
+
+        // The layout the positions of the clusters of a mesh are taken from
+        public enum MeshLayout
+        {
+            Graph,
+            Tree,
+        }
+
+        // What the size of the sphere of a cluster shows
+        public enum NodeSizing
+        {
+            Radius,
+            Cardinality,
+        }
+
+        public enum ClusterColoring
+        {
+            Uniform,
+            DominantLabel,
+            Entropy,
+            DistanceToQuery,
+        }
+
+        // The text drawn next to every cluster of a rendered tree
+        public enum NodeLabel
+        {
+            None,
+            Name,
+            Cardinality,
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        public struct MeshExportOptions
+        {
+            public MeshLayout layout;
+            public ClusterColoring coloring;
+            public NodeSizing sizing;
+            // The radius of the sphere of the root, which every other sphere is scaled from
+            public float nodeScale;
+            // The deepest clusters drawn in the tree layout. Negative values draw the whole tree
+            public int maxDepth;
+        }
+
+        [StructLayout(LayoutKind.Sequential)]
+        public struct TreeRenderOptions
+        {
+            public ClusterColoring coloring;
+            public NodeLabel label;
+            // The deepest clusters drawn. Negative values draw the whole tree
+            public int maxDepth;
+            // The distance between levels and the smallest distance between clusters, in pixels
+            public float spacing;
+            // The radius of the circle of every cluster, in pixels
+            public float nodeRadius;
+            // How much larger a png is than the svg it is rasterized from
+            public float pngScale;
+        }
+    }
+}
\ No newline at end of file
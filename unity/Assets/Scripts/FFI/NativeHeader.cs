@@ -28,11 +28,32 @@ namespace Clam
             [DllImport(__DllName, EntryPoint = "shutdown_clam", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
             private static extern FFIError shutdown_clam(out IntPtr ptr);
 
+            [DllImport(__DllName, EntryPoint = "register_distance_function", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError register_distance_function(string name, DistanceFunction callback, [MarshalAs(UnmanagedType.U1)] bool isExpensive);
+
+            [DllImport(__DllName, EntryPoint = "unregister_distance_function", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError unregister_distance_function(string name);
+
             // -------------------------------------  File IO ------------------------------------- 
 
             [DllImport(__DllName, EntryPoint = "save_cakes", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
             private static extern FFIError save_cakes(IntPtr ptr, byte[] file_name, int name_len);
 
+            [DllImport(__DllName, EntryPoint = "save_graph_layout", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError save_graph_layout(IntPtr ptr, byte[] file_name, int name_len);
+
+            [DllImport(__DllName, EntryPoint = "load_graph_layout", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError load_graph_layout(IntPtr ptr, byte[] file_name, int name_len, NodeVisitor cluster_selector);
+
+            [DllImport(__DllName, EntryPoint = "export_graph", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError export_graph(IntPtr ptr, byte[] file_name, int name_len, GraphFormat format);
+
+            [DllImport(__DllName, EntryPoint = "export_mesh", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError export_mesh(IntPtr ptr, byte[] file_name, int name_len, MeshFormat format, ref MeshExportOptions options);
+
+            [DllImport(__DllName, EntryPoint = "render_tree", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError render_tree(IntPtr ptr, byte[] file_name, int name_len, ImageFormat format, ref TreeRenderOptions options);
+
             // -------------------------------------  Tree helpers ------------------------------------- 
 
             [DllImport(__DllName, EntryPoint = "for_each_dft", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
@@ -50,6 +71,10 @@ namespace Clam
             [DllImport(__DllName, EntryPoint = "max_lfd", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
             private static extern float max_lfd(IntPtr handle);
 
+            [DllImport(__DllName, EntryPoint = "has_labels", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            [return: MarshalAs(UnmanagedType.U1)]
+            private static extern bool has_labels(IntPtr handle);
+
             [DllImport(__DllName, EntryPoint = "max_vertex_degree", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
             private static extern int max_vertex_degree(IntPtr handle);
 
@@ -102,11 +127,89 @@ namespace Clam
             [DllImport(__DllName, EntryPoint = "draw_hierarchy_offset_from", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
             private static extern FFIError draw_hierarchy_offset_from(IntPtr ptr, ref ClusterData offsetPos, int currentDepth, int maxDepth, NodeVisitor callback);
 
+            // ------------------------------------- Graph Selection -------------------------------------
+
+            [DllImport(__DllName, EntryPoint = "init_clam_graph_at_depth", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError init_clam_graph_at_depth(IntPtr ptr, int depth, NodeVisitor cluster_selector);
+
+            [DllImport(__DllName, EntryPoint = "init_clam_graph_leaf_cut", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError init_clam_graph_leaf_cut(IntPtr ptr, int targetCount, NodeVisitor cluster_selector);
+
+            [DllImport(__DllName, EntryPoint = "init_clam_graph_by_threshold", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError init_clam_graph_by_threshold(IntPtr ptr, float maxRadius, float maxLfd, NodeVisitor cluster_selector);
+
+            [DllImport(__DllName, EntryPoint = "init_clam_graph_from_ids", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError init_clam_graph_from_ids(IntPtr ptr, string[] ids, int count, NodeVisitor cluster_selector);
+
+            [DllImport(__DllName, EntryPoint = "save_clam_graph", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError save_clam_graph(IntPtr ptr, string name);
+
+            [DllImport(__DllName, EntryPoint = "switch_clam_graph", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError switch_clam_graph(IntPtr ptr, string name, NodeVisitor cluster_selector);
+
+            [DllImport(__DllName, EntryPoint = "remove_clam_graph", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError remove_clam_graph(IntPtr ptr, string name);
+
             // ------------------------------------- Graph Physics -------------------------------------
             [System.Security.SecurityCritical]
             [DllImport(__DllName, EntryPoint = "init_force_directed_graph", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
             private static unsafe extern FFIError init_force_directed_graph(IntPtr handle, float scalar, int maxIters);
 
+            [System.Security.SecurityCritical]
+            [DllImport(__DllName, EntryPoint = "init_force_directed_graph_with_settings", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static unsafe extern FFIError init_force_directed_graph_with_settings(IntPtr handle, float scalar, int maxIters, ref PhysicsSettings settings);
+
+            [DllImport(__DllName, EntryPoint = "set_physics_settings", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError set_physics_settings(IntPtr handle, ref PhysicsSettings settings);
+
+            [DllImport(__DllName, EntryPoint = "get_physics_settings", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError get_physics_settings(IntPtr handle, out PhysicsSettings settings);
+
+            [DllImport(__DllName, EntryPoint = "pause_physics", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError pause_physics(IntPtr handle);
+
+            [DllImport(__DllName, EntryPoint = "resume_physics", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError resume_physics(IntPtr handle);
+
+            [DllImport(__DllName, EntryPoint = "step_physics", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError step_physics(IntPtr handle, int frames);
+
+            [DllImport(__DllName, EntryPoint = "restart_physics", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError restart_physics(IntPtr handle, int maxIters);
+
+            [DllImport(__DllName, EntryPoint = "physics_update_positions", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError physics_update_positions(IntPtr handle, [Out] float[] outPositions, int len);
+
+            [DllImport(__DllName, EntryPoint = "copy_physics_positions", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError copy_physics_positions(IntPtr handle, [Out] float[] outPositions, int len);
+
+            [DllImport(__DllName, EntryPoint = "get_physics_node_count", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern int get_physics_node_count(IntPtr handle);
+
+            [DllImport(__DllName, EntryPoint = "visit_physics_node_order", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError visit_physics_node_order(IntPtr handle, NodeVisitor visitor);
+
+            [DllImport(__DllName, EntryPoint = "pin_node", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError pin_node(IntPtr handle, string cluster_id, Vec3 position);
+
+            [DllImport(__DllName, EntryPoint = "move_pinned_node", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError move_pinned_node(IntPtr handle, string cluster_id, Vec3 position);
+
+            [DllImport(__DllName, EntryPoint = "unpin_node", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError unpin_node(IntPtr handle, string cluster_id);
+
+            [DllImport(__DllName, EntryPoint = "get_energy_history_len", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern int get_energy_history_len(IntPtr handle);
+
+            [DllImport(__DllName, EntryPoint = "get_energy_history", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError get_energy_history(IntPtr handle, [Out] FrameEnergy[] outEnergy, int len);
+
+            [DllImport(__DllName, EntryPoint = "get_converged_iteration", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern int get_converged_iteration(IntPtr handle);
+
+            [DllImport(__DllName, EntryPoint = "get_distance_cache_stats", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+            private static extern FFIError get_distance_cache_stats(IntPtr handle, out DistanceCacheStats stats);
+
             [System.Security.SecurityCritical]
             [DllImport(__DllName, EntryPoint = "init_graph_vertices", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
             private static unsafe extern void init_graph_vertices(IntPtr handle, NodeVisitorMut edge_cb);
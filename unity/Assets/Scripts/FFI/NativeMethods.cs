@@ -15,6 +15,8 @@ namespace Clam
         public unsafe delegate void NodeVisitor(ref Clam.FFI.ClusterData baton);
         public unsafe delegate void NameSetter(ref Clam.FFI.ClusterIDs baton);
         public unsafe delegate void NodeVisitorMut(ref Clam.FFI.ClusterData inData);
+        // Takes pointers to two vectors of the given length. Keep the delegate alive while it is registered
+        public unsafe delegate float DistanceFunction(float* x, float* y, int len);
 
         public static partial class NativeMethods
         {
@@ -72,6 +74,16 @@ namespace Clam
                 return FFIError.NullPointerPassed;
             }
 
+            public static FFIError RegisterDistanceFunction(string name, DistanceFunction callback, bool isExpensive)
+            {
+                return register_distance_function(name, callback, isExpensive);
+            }
+
+            public static FFIError UnregisterDistanceFunction(string name)
+            {
+                return unregister_distance_function(name);
+            }
+
             // -------------------------------------  File IO ------------------------------------- 
 
             public static FFIError SaveCakes(string dataName)
@@ -105,6 +117,36 @@ namespace Clam
                 }
             }
 
+            public static FFIError SaveGraphLayout(string fileName)
+            {
+                byte[] byteName = Encoding.UTF8.GetBytes(fileName);
+                return save_graph_layout(m_Handle, byteName, byteName.Length);
+            }
+
+            public static FFIError LoadGraphLayout(string fileName, NodeVisitor clusterSelector)
+            {
+                byte[] byteName = Encoding.UTF8.GetBytes(fileName);
+                return load_graph_layout(m_Handle, byteName, byteName.Length, clusterSelector);
+            }
+
+            public static FFIError ExportGraph(string fileName, GraphFormat format)
+            {
+                byte[] byteName = Encoding.UTF8.GetBytes(fileName);
+                return export_graph(m_Handle, byteName, byteName.Length, format);
+            }
+
+            public static FFIError ExportMesh(string fileName, MeshFormat format, MeshExportOptions options)
+            {
+                byte[] byteName = Encoding.UTF8.GetBytes(fileName);
+                return export_mesh(m_Handle, byteName, byteName.Length, format, ref options);
+            }
+
+            public static FFIError RenderTree(string fileName, ImageFormat format, TreeRenderOptions options)
+            {
+                byte[] byteName = Encoding.UTF8.GetBytes(fileName);
+                return render_tree(m_Handle, byteName, byteName.Length, format, ref options);
+            }
+
             // -------------------------------------  Tree helpers ------------------------------------- 
 
             public static FFIError ForEachDFT(NodeVisitor callback, string startNode = "root", int maxDepth = -1)
@@ -153,6 +195,11 @@ namespace Clam
                 return tree_cardinality(m_Handle);
             }
 
+            public static bool HasLabels()
+            {
+                return has_labels(m_Handle);
+            }
+
             public static FFIError ColorClustersByEntropy(NodeVisitor callback)
             {
                 return color_clusters_by_entropy(m_Handle, callback);
@@ -256,11 +303,142 @@ namespace Clam
                 return draw_hierarchy_offset_from(m_Handle, ref nodeData, currentDepth, maxDepth - rootDepth, callback);
             }
 
+            // Graph Selection
+            public static FFIError InitClamGraphAtDepth(int depth, NodeVisitor clusterSelector)
+            {
+                return init_clam_graph_at_depth(m_Handle, depth, clusterSelector);
+            }
+
+            public static FFIError InitClamGraphLeafCut(int targetCount, NodeVisitor clusterSelector)
+            {
+                return init_clam_graph_leaf_cut(m_Handle, targetCount, clusterSelector);
+            }
+
+            public static FFIError InitClamGraphByThreshold(float maxRadius, float maxLfd, NodeVisitor clusterSelector)
+            {
+                return init_clam_graph_by_threshold(m_Handle, maxRadius, maxLfd, clusterSelector);
+            }
+
+            public static FFIError InitClamGraphFromIDs(string[] ids, NodeVisitor clusterSelector)
+            {
+                return init_clam_graph_from_ids(m_Handle, ids, ids.Length, clusterSelector);
+            }
+
+            public static FFIError SaveClamGraph(string name)
+            {
+                return save_clam_graph(m_Handle, name);
+            }
+
+            public static FFIError SwitchClamGraph(string name, NodeVisitor clusterSelector)
+            {
+                return switch_clam_graph(m_Handle, name, clusterSelector);
+            }
+
+            public static FFIError RemoveClamGraph(string name)
+            {
+                return remove_clam_graph(m_Handle, name);
+            }
+
             // Graph Physics
             public static FFIError InitForceDirectedGraph(float scalar, int maxIters)
             {
                 return init_force_directed_graph(m_Handle, scalar, maxIters);
             }
+
+            public static FFIError InitForceDirectedGraph(float scalar, int maxIters, PhysicsSettings settings)
+            {
+                return init_force_directed_graph_with_settings(m_Handle, scalar, maxIters, ref settings);
+            }
+
+            public static FFIError SetPhysicsSettings(PhysicsSettings settings)
+            {
+                return set_physics_settings(m_Handle, ref settings);
+            }
+
+            public static FFIError GetPhysicsSettings(out PhysicsSettings settings)
+            {
+                return get_physics_settings(m_Handle, out settings);
+            }
+
+            public static FFIError PausePhysics()
+            {
+                return pause_physics(m_Handle);
+            }
+
+            public static FFIError ResumePhysics()
+            {
+                return resume_physics(m_Handle);
+            }
+
+            public static FFIError StepPhysics(int frames)
+            {
+                return step_physics(m_Handle, frames);
+            }
+
+            public static FFIError RestartPhysics(int maxIters)
+            {
+                return restart_physics(m_Handle, maxIters);
+            }
+
+            // positions holds x, y and z for every node, in the order of VisitPhysicsNodeOrder
+            public static FFIError PhysicsUpdatePositions(float[] positions)
+            {
+                return physics_update_positions(m_Handle, positions, positions.Length);
+            }
+
+            public static FFIError CopyPhysicsPositions(float[] positions)
+            {
+                return copy_physics_positions(m_Handle, positions, positions.Length);
+            }
+
+            // -1 if no physics
+            public static int GetPhysicsNodeCount()
+            {
+                return get_physics_node_count(m_Handle);
+            }
+
+            public static FFIError VisitPhysicsNodeOrder(NodeVisitor visitor)
+            {
+                return visit_physics_node_order(m_Handle, visitor);
+            }
+
+            public static FFIError PinNode(string clusterID, Vector3 position)
+            {
+                return pin_node(m_Handle, clusterID, new Vec3(position));
+            }
+
+            public static FFIError MovePinnedNode(string clusterID, Vector3 position)
+            {
+                return move_pinned_node(m_Handle, clusterID, new Vec3(position));
+            }
+
+            public static FFIError UnpinNode(string clusterID)
+            {
+                return unpin_node(m_Handle, clusterID);
+            }
+
+            public static FFIError GetEnergyHistory(out FrameEnergy[] energy)
+            {
+                int len = get_energy_history_len(m_Handle);
+                if (len < 0)
+                {
+                    energy = new FrameEnergy[0];
+                    return FFIError.NoEnergyHistory;
+                }
+                energy = new FrameEnergy[len];
+                return get_energy_history(m_Handle, energy, len);
+            }
+
+            // -1 if the physics has not converged
+            public static int GetConvergedIteration()
+            {
+                return get_converged_iteration(m_Handle);
+            }
+
+            public static FFIError GetDistanceCacheStats(out DistanceCacheStats stats)
+            {
+                return get_distance_cache_stats(m_Handle, out stats);
+            }
             public static void InitGraphVertices(NodeVisitorMut edgeCB)
             {
                 init_graph_vertices(m_Handle, edgeCB);
@@ -0,0 +1,20 @@
+using System.Runtime.InteropServices;
+
+namespace Clam
+{
+    namespace FFI
+    {
+        // Must match the field order of PartitionCriteriaFFI in clam_ffi. Criteria that are zero or
+        // negative are not checked
+        [StructLayout(LayoutKind.Sequential)]
+        public struct PartitionCriteriaFFI
+        {
+            [MarshalAs(UnmanagedType.U1)]
+            public bool checkAny;
+            public int maxDepth;
+            public float minRadius;
+            public float minLfd;
+            public long seed;
+        }
+    }
+}
\ No newline at end of file
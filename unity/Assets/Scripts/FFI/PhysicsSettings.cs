@@ -0,0 +1,81 @@
+using System.Runtime.InteropServices;
+
+namespace Clam
+{
+    namespace FFI
+    {
+        // The forces that spread out clusters that are not joined by an edge. Must match ForceModel
+        // in clam_ffi
+        public enum ForceModel
+        {
+            RandomSprings,
+            BarnesHut,
+        }
+
+        // How the mass of the node of a cluster is chosen. Must match MassModel in clam_ffi
+        public enum MassModel
+        {
+            Cardinality,
+            LogCardinality,
+            Uniform,
+        }
+
+        // Must match the field order of PhysicsSettings in clam_ffi
+        [StructLayout(LayoutKind.Sequential)]
+        public struct PhysicsSettings
+        {
+            public float friction;
+            public float maxSpeed;
+            public float springConstant;
+            public int randomPartners;
+            public MassModel massModel;
+            public ForceModel forceModel;
+            public float timestep;
+            public float convergenceThreshold;
+
+            // The settings clam_ffi uses when none are passed
+            public static PhysicsSettings Default()
+            {
+                return new PhysicsSettings
+                {
+                    friction = 0.98f,
+                    maxSpeed = 5.0f,
+                    springConstant = 0.005f,
+                    randomPartners = 3,
+                    massModel = MassModel.Cardinality,
+                    forceModel = ForceModel.RandomSprings,
+                    timestep = 1.0f,
+                    convergenceThreshold = 1e-4f,
+                };
+            }
+        }
+
+        // The energy of a force-directed graph after a frame. Must match FrameEnergy in clam_ffi
+        [StructLayout(LayoutKind.Sequential)]
+        public struct FrameEnergy
+        {
+            public float kinetic;
+            public float spring;
+
+            public float Total { get { return kinetic + spring; } }
+        }
+
+        public enum DistanceCacheKind
+        {
+            Matrix,
+            Lru,
+        }
+
+        // Must match the field order of DistanceCacheStats in clam_ffi
+        [StructLayout(LayoutKind.Sequential)]
+        public struct DistanceCacheStats
+        {
+            public DistanceCacheKind kind;
+            public ulong hits;
+            public ulong metricCalls;
+            public ulong entries;
+            public ulong capacity;
+            public float hitRate;
+        }
+    }
+}
\ No newline at end of file
@@ -7,31 +7,92 @@ namespace Clam
 {
     namespace FFI
     {
+        // Must match the field order of TreeStartupDataFFI in clam_ffi. Rust bools are one byte
         [StructLayout(LayoutKind.Sequential)]
         public struct TreeStartupDataFFI : IRustResource
         {
             public StringFFI dataName;
             public DistanceMetric distanceMetric;
             public uint cardinality;
+            [MarshalAs(UnmanagedType.U1)]
             public bool isExpensive;
+            [MarshalAs(UnmanagedType.U1)]
+            public bool shouldLoad;
+            public StringFFI dataPath;
+            public StringFFI labelsPath;
+            public int labelColumn;
+            public byte delimiter;
+            [MarshalAs(UnmanagedType.U1)]
+            public bool noHeader;
+            [MarshalAs(UnmanagedType.U1)]
+            public bool featuresOnly;
+            public DataType dataType;
+            public float minkowskiP;
+            public StringFFI customMetric;
+            public PartitionCriteriaFFI partitionCriteria;
 
             public static (TreeStartupDataFFI, FFIError) Alloc(TreeStartupData data)
             {
-                (var resource, var result) = StringFFI.Alloc(data.dataName);
-                
                 TreeStartupDataFFI outData = new TreeStartupDataFFI();
-                outData.dataName = resource;
                 outData.distanceMetric = data.distanceMetric;
                 outData.cardinality = data.cardinality;
                 outData.isExpensive = data.isExpensive;
+                outData.shouldLoad = data.shouldLoad;
+                outData.labelColumn = data.labelColumn;
+                outData.delimiter = (byte)data.delimiter;
+                outData.noHeader = data.noHeader;
+                outData.featuresOnly = data.featuresOnly;
+                outData.dataType = data.dataType;
+                outData.minkowskiP = data.minkowskiP;
+                outData.partitionCriteria.checkAny = data.checkAny;
+                outData.partitionCriteria.maxDepth = data.maxDepth;
+                outData.partitionCriteria.minRadius = data.minRadius;
+                outData.partitionCriteria.minLfd = data.minLfd;
+                outData.partitionCriteria.seed = data.seed;
+
+                var result = NativeMethods.AllocString(data.dataName, out outData.dataName);
+                if (result == FFIError.Ok)
+                {
+                    result = AllocOptional(data.dataPath, out outData.dataPath);
+                }
+                if (result == FFIError.Ok)
+                {
+                    result = AllocOptional(data.labelsPath, out outData.labelsPath);
+                }
+                if (result == FFIError.Ok)
+                {
+                    result = AllocOptional(data.customMetric, out outData.customMetric);
+                }
 
                 return (outData, result);
             }
 
+            // Empty strings are left null, which Rust reads as unset
+            private static FFIError AllocOptional(string value, out StringFFI resource)
+            {
+                resource = new StringFFI();
+                if (string.IsNullOrEmpty(value))
+                {
+                    return FFIError.Ok;
+                }
+                return NativeMethods.AllocString(value, out resource);
+            }
+
+            private static void FreeOptional(ref StringFFI resource)
+            {
+                if (!resource.IsNull)
+                {
+                    NativeMethods.FreeString(ref resource);
+                }
+            }
+
             public void Free()
             {
-                NativeMethods.FreeString(ref dataName);
+                FreeOptional(ref dataName);
+                FreeOptional(ref dataPath);
+                FreeOptional(ref labelsPath);
+                FreeOptional(ref customMetric);
             }
         }
     }
-}
\ No newline at end of file
+}
\ No newline at end of file